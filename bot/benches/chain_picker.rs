@@ -9,7 +9,10 @@ use core::{
     tumo::{Tumo, Tumos},
 };
 
-use nova_bot::chain_picker::{enumerate_fireable_chains, strategies::*, ChainPicker};
+use nova_bot::{
+    chain_picker::{enumerate_fireable_chains, strategies::*, ChainPicker},
+    OpponentAnalysis,
+};
 use test::Bencher;
 
 // 5,842,780 ns/iter (+/- 725,681)
@@ -67,11 +70,13 @@ fn bench_strategies_houwa(b: &mut Bencher) {
     let player_state_1p = PlayerState::new(board.clone(), tumos.clone(), 0, 0, 0, 0, 0, 0);
     let player_state_2p = PlayerState::new(board.clone(), tumos.clone(), 0, 0, 0, 0, 0, 0);
     let decisions = enumerate_fireable_chains(&player_state_1p.clone());
+    let opponent = OpponentAnalysis::new(&player_state_2p);
 
     b.iter(|| {
         test::black_box(Houwa::pick_chain(
             &player_state_1p.clone(),
             Some(&player_state_2p.clone()),
+            Some(&opponent),
             &decisions.clone(),
         ))
     });
@@ -6,18 +6,23 @@ use core::{
     player_state::PlayerState,
     tumo::{Tumo, Tumos},
 };
+use std::sync::Arc;
 
 use nova_bot::{
     evaluator::BUILD,
-    searcher::{MonteCarloBeamSearcher, Searcher},
+    searcher::{ChokudaiSearcher, DfsSearcher, MonteCarloBeamSearcher, SearchLimit, Searcher},
+    WorkerPool,
 };
 use test::Bencher;
 
 extern crate test;
 
-// 31,104,760 ns/iter (+/- 2,006,063)
+// Every searcher runs its fixed-size search under `SearchLimit::default()`, so that the
+// numbers below are comparable with each other.
+
+// 2,159,601 ns/iter (+/- 651,976)
 #[bench]
-fn bench_monte_carlo_beam_search_frame_2(b: &mut Bencher) {
+fn bench_monte_carlo_beam_search(b: &mut Bencher) {
     let board = Board::from(concat!(
         "G.....", // 4
         "GG..Y.", // 3
@@ -31,18 +36,21 @@ fn bench_monte_carlo_beam_search_frame_2(b: &mut Bencher) {
     ]);
     let player_state = PlayerState::new(board, tumos, 1, 2, 3, 4, 5, 0);
 
+    let searcher = MonteCarloBeamSearcher::new(Arc::new(WorkerPool::default()));
+
+    // fixed number of samples
     b.iter(|| {
-        test::black_box(MonteCarloBeamSearcher::search(
+        test::black_box(searcher.search(
             &player_state.clone(),
             &BUILD.clone(),
-            Some(2),
+            &SearchLimit::default(),
         ))
     })
 }
 
-// 123,285,540 ns/iter (+/- 4,684,603)
+// 6,047,558 ns/iter (+/- 829,583)
 #[bench]
-fn bench_monte_carlo_beam_search_frame_8(b: &mut Bencher) {
+fn bench_chokudai_search(b: &mut Bencher) {
     let board = Board::from(concat!(
         "G.....", // 4
         "GG..Y.", // 3
@@ -56,18 +64,19 @@ fn bench_monte_carlo_beam_search_frame_8(b: &mut Bencher) {
     ]);
     let player_state = PlayerState::new(board, tumos, 1, 2, 3, 4, 5, 0);
 
+    // fixed number of passes
     b.iter(|| {
-        test::black_box(MonteCarloBeamSearcher::search(
+        test::black_box(ChokudaiSearcher.search(
             &player_state.clone(),
             &BUILD.clone(),
-            Some(8),
+            &SearchLimit::default(),
         ))
     })
 }
 
-// 362,722,770 ns/iter (+/- 11,512,221)
+// 22,814,316 ns/iter (+/- 2,951,493)
 #[bench]
-fn bench_monte_carlo_beam_search_frame_24(b: &mut Bencher) {
+fn bench_dfs_search(b: &mut Bencher) {
     let board = Board::from(concat!(
         "G.....", // 4
         "GG..Y.", // 3
@@ -82,10 +91,10 @@ fn bench_monte_carlo_beam_search_frame_24(b: &mut Bencher) {
     let player_state = PlayerState::new(board, tumos, 1, 2, 3, 4, 5, 0);
 
     b.iter(|| {
-        test::black_box(MonteCarloBeamSearcher::search(
+        test::black_box(DfsSearcher.search(
             &player_state.clone(),
             &BUILD.clone(),
-            Some(24),
+            &SearchLimit::default(),
         ))
     })
 }
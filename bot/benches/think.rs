@@ -27,7 +27,9 @@ fn bench_think_1(b: &mut Bencher) {
     ]);
     let player_state: DetailedPlayerState = PlayerState::new(board, tumos, 1, 2, 3, 4, 5, 0).into();
 
-    b.iter(|| test::black_box(Nova::default().think(&player_state.clone(), None, None)));
+    let nova = Nova::default();
+
+    b.iter(|| test::black_box(nova.think(&player_state.clone(), None, None)));
 }
 
 // (2,  22):  28,357,450 ns/iter (+/-   780,026)
@@ -51,5 +53,7 @@ fn bench_think_2(b: &mut Bencher) {
     ]);
     let player_state: DetailedPlayerState = PlayerState::new(board, tumos, 1, 2, 3, 4, 5, 0).into();
 
-    b.iter(|| test::black_box(Nova::default().think(&player_state.clone(), None, None)));
+    let nova = Nova::default();
+
+    b.iter(|| test::black_box(nova.think(&player_state.clone(), None, None)));
 }
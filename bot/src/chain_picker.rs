@@ -4,13 +4,52 @@ pub mod strategies;
 use core::player_state::PlayerState;
 
 pub use enumerate::enumerate_fireable_chains;
+use serde::{Deserialize, Serialize};
+use strategies::*;
 
-use crate::decision::Decision;
+use crate::{decision::Decision, OpponentAnalysis};
 
 pub trait ChainPicker {
+    /// `opponent` is the analysis of `player_state_2p`.
     fn pick_chain(
         player_state_1p: &PlayerState,
         player_state_2p: Option<&PlayerState>,
+        opponent: Option<&OpponentAnalysis>,
         chains: &[Decision],
     ) -> Option<Decision>;
 }
+
+/// [ChainPicker] selectable at runtime (e.g. from [crate::NovaConfig]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainPickerKind {
+    Houwa,
+    Taiou,
+    Tsubushi,
+    Zenkeshi,
+}
+
+impl ChainPickerKind {
+    pub fn pick_chain(
+        &self,
+        player_state_1p: &PlayerState,
+        player_state_2p: Option<&PlayerState>,
+        opponent: Option<&OpponentAnalysis>,
+        chains: &[Decision],
+    ) -> Option<Decision> {
+        match self {
+            ChainPickerKind::Houwa => {
+                Houwa::pick_chain(player_state_1p, player_state_2p, opponent, chains)
+            }
+            ChainPickerKind::Taiou => {
+                Taiou::pick_chain(player_state_1p, player_state_2p, opponent, chains)
+            }
+            ChainPickerKind::Tsubushi => {
+                Tsubushi::pick_chain(player_state_1p, player_state_2p, opponent, chains)
+            }
+            ChainPickerKind::Zenkeshi => {
+                Zenkeshi::pick_chain(player_state_1p, player_state_2p, opponent, chains)
+            }
+        }
+    }
+}
@@ -58,21 +58,24 @@ pub fn enumerate_fireable_chains(player_state: &PlayerState) -> Vec<Decision> {
     candidate_chains.sort_by(|a, b| a.chain.frame().cmp(&b.chain.frame()));
 
     // take only if score is greater than the previous one (using in-memory swapping for performance sake)
+    // all-clears are compared separately since the bonus is not counted in the score
     let mut tail = 0;
-    for i in 1..candidate_chains.len() {
-        let prv_score = candidate_chains[tail].chain.score();
-        let cur_score = candidate_chains[i].chain.score();
+    let mut max_scores = [None; 2];
+    for i in 0..candidate_chains.len() {
+        let chain = &candidate_chains[i].chain;
+        let max_score = &mut max_scores[chain.zenkeshi() as usize];
 
-        if prv_score < cur_score {
-            tail += 1;
+        if *max_score < Some(chain.score()) {
+            *max_score = Some(chain.score());
 
             // let candidate_chains[tail] be the last one
             if tail != i {
                 candidate_chains.swap(tail, i);
             }
+            tail += 1;
         }
     }
-    candidate_chains.resize(tail + 1, Decision::default());
+    candidate_chains.truncate(tail);
 
     candidate_chains
 }
@@ -87,6 +90,9 @@ impl Node {
         let fired = new_player_state.board.simulate();
         new_player_state.frame += place_frame + fired.frame();
         new_player_state.carry_over += bonus;
+        if fired.chain() > 0 {
+            new_player_state.zenkeshi = fired.zenkeshi();
+        }
 
         let mut new_placements = self.1.clone();
         new_placements.push(*placement);
@@ -135,17 +141,24 @@ mod tests {
 
         let decisions = enumerate_fireable_chains(&player_state);
 
-        assert_eq!(decisions.len(), 10);
-        // should be sorted by both frame and score
+        assert_eq!(decisions.len(), 12);
+        // should be sorted by both frame and score, apart from all-clears
         assert!(is_sorted(decisions.iter().map(|d| d.chain.frame())));
-        assert!(is_sorted(decisions.iter().map(|d| d.chain.score())));
+        for zenkeshi in [false, true] {
+            assert!(is_sorted(
+                decisions
+                    .iter()
+                    .filter(|d| d.chain.zenkeshi() == zenkeshi)
+                    .map(|d| d.chain.score())
+            ));
+        }
         // smallest chain
         let smallest = decisions.first().unwrap();
         assert_eq!(smallest.chain, Chain::new(1, 40, 103));
         assert_eq!(smallest.placements[0], Placement::new(4, 3));
         // biggest chain
         let biggest = decisions.last().unwrap();
-        assert_eq!(biggest.chain, Chain::new(5, 4840, 525));
+        assert_eq!(biggest.chain, Chain::new(5, 4840, 527).with_zenkeshi(true));
         assert_eq!(biggest.placements[0], Placement::new(3, 0));
         assert_eq!(biggest.placements[1], Placement::new(4, 2));
         assert_eq!(biggest.placements[2], Placement::new(5, 0));
@@ -1,3 +1,9 @@
 mod houwa;
+mod taiou;
+mod tsubushi;
+mod zenkeshi;
 
 pub use houwa::Houwa;
+pub use taiou::Taiou;
+pub use tsubushi::Tsubushi;
+pub use zenkeshi::Zenkeshi;
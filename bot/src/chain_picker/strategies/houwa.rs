@@ -1,25 +1,94 @@
 use core::player_state::PlayerState;
 
-use crate::{chain_picker::ChainPicker, decision::Decision};
+use crate::{chain_picker::ChainPicker, decision::Decision, OpponentAnalysis};
+
+/// Fire regardless of the opponent if the chain is this big.
+const HOUWA_SCORE: u32 = 85000;
 
 pub struct Houwa;
 
 impl ChainPicker for Houwa {
     fn pick_chain(
-        _player_state_1p: &PlayerState,
+        player_state_1p: &PlayerState,
         _player_state_2p: Option<&PlayerState>,
+        opponent: Option<&OpponentAnalysis>,
         chains: &[Decision],
     ) -> Option<Decision> {
         // TODO: refine
-        chains
-            .iter()
-            .max_by(|a, b| {
-                a.chain
-                    .chain()
-                    .cmp(&b.chain.chain())
-                    .then_with(|| a.chain.score().cmp(&b.chain.score()))
-            })
-            .cloned()
-            .filter(|d| d.chain.score() >= 85000)
+        let best = chains.iter().max_by(|a, b| {
+            a.chain
+                .chain()
+                .cmp(&b.chain.chain())
+                .then_with(|| a.chain.score().cmp(&b.chain.score()))
+        })?;
+
+        if best.chain.score() >= HOUWA_SCORE {
+            return Some(best.clone());
+        }
+
+        // fire if the opponent cannot survive even with what they can answer during our chain
+        let opponent = opponent?;
+        let ojama = player_state_1p.clone().send_ojama(best.chain.score());
+        let answer = opponent.max_ojama_within(best.chain.frame());
+        if ojama >= answer + opponent.ojama_to_kill {
+            return Some(best.clone());
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{
+        board::Board,
+        color::PuyoColor::*,
+        tumo::{Tumo, Tumos},
+    };
+
+    use super::*;
+    use crate::chain_picker::enumerate_fireable_chains;
+
+    #[test]
+    fn relative_to_opponent() {
+        // 2 chain sending 6 ojamas
+        let board = Board::from(concat!(
+            "....B.", // 4
+            "....RB", // 3
+            "....RB", // 2
+            "....RB", // 1
+        ));
+        let tumos = Tumos::new(&[Tumo::new(RED, RED), Tumo::new(YELLOW, GREEN)]);
+        let player_state_1p = PlayerState::new(board, tumos, 0, 0, 0, 0, 0, 0);
+        let chains = enumerate_fireable_chains(&player_state_1p);
+
+        let pick = |board_2p: Board| {
+            let tumos = Tumos::new(&[Tumo::new(YELLOW, GREEN), Tumo::new(YELLOW, GREEN)]);
+            let player_state_2p = PlayerState::new(board_2p, tumos, 0, 0, 0, 0, 0, 0);
+            let opponent = OpponentAnalysis::new(&player_state_2p);
+            Houwa::pick_chain(
+                &player_state_1p,
+                Some(&player_state_2p),
+                Some(&opponent),
+                &chains,
+            )
+        };
+
+        assert!(pick(Board::new()).is_none());
+        assert!(pick(Board::from(concat!(
+            "..o...", // 11
+            "..o...", // 10
+            "..o...", // 9
+            "..o...", // 8
+            "..o...", // 7
+            "..o...", // 6
+            "..o...", // 5
+            "..o...", // 4
+            "..o...", // 3
+            "..o...", // 2
+            "..o...", // 1
+        )))
+        .is_some());
+        assert!(Houwa::pick_chain(&player_state_1p, None, None, &chains).is_none());
     }
 }
@@ -0,0 +1,167 @@
+use core::{board::WIDTH, player_state::PlayerState};
+
+use crate::{
+    chain_picker::ChainPicker, decision::Decision, opponent_analysis::FRAMES_PER_TUMO,
+    OpponentAnalysis,
+};
+
+/// Less than a row of ojama is not worth spending a chain.
+const TOLERABLE_OJAMA: u32 = WIDTH as u32 - 1;
+
+/// Respond to the pending ojama.
+///
+/// Fire the cheapest chain which fully offsets it if any.
+/// Otherwise fire the chain which minimizes the damage only if it's almost harmless or taking the hit kills.
+pub struct Taiou;
+
+impl ChainPicker for Taiou {
+    fn pick_chain(
+        player_state_1p: &PlayerState,
+        _player_state_2p: Option<&PlayerState>,
+        opponent: Option<&OpponentAnalysis>,
+        chains: &[Decision],
+    ) -> Option<Decision> {
+        let pending = player_state_1p.ojama_pending();
+        if pending == 0 {
+            return None;
+        }
+
+        // incoming ojama is fixed when the opponent's ongoing chain ends
+        let deadline = opponent
+            .and_then(|o| o.ongoing_chain.as_ref())
+            .map_or(u32::MAX, |c| c.frame);
+
+        // fixed ojama drops right after the next placement unless it fires
+        let candidates: Vec<(&Decision, u32)> = chains
+            .iter()
+            .filter(|d| player_state_1p.ojama_fixed == 0 || d.placements.len() == 1)
+            .filter(|d| (d.placements.len() as u32 - 1) * FRAMES_PER_TUMO <= deadline)
+            .map(|d| (d, remaining_ojama(player_state_1p, d)))
+            .collect();
+
+        // chains are sorted by score, so the first one is the cheapest
+        if let Some((decision, _)) = candidates.iter().find(|(_, rest)| *rest == 0) {
+            return Some((*decision).clone());
+        }
+
+        if pending <= TOLERABLE_OJAMA {
+            return None;
+        }
+
+        let (decision, rest) = candidates
+            .into_iter()
+            .min_by_key(|(d, rest)| (*rest, d.chain.frame()))?;
+        if rest <= TOLERABLE_OJAMA || is_lethal(player_state_1p, pending) {
+            Some(decision.clone())
+        } else {
+            // keep the chain and take the hit
+            None
+        }
+    }
+}
+
+/// Ojama left after offsetting with `decision`.
+fn remaining_ojama(player_state: &PlayerState, decision: &Decision) -> u32 {
+    let mut player_state = player_state.clone();
+    player_state.send_ojama(decision.chain.score());
+    player_state.ojama_pending()
+}
+
+fn is_lethal(player_state: &PlayerState, ojama: u32) -> bool {
+    let mut board = player_state.board.clone();
+    board.drop_ojama_rows(ojama as usize);
+    board.is_dead()
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{
+        board::Board,
+        color::PuyoColor::*,
+        tumo::{Tumo, Tumos},
+    };
+
+    use super::*;
+    use crate::chain_picker::enumerate_fireable_chains;
+
+    fn player_state(board: Board, ojama_fixed: u32, ojama_incoming: u32) -> PlayerState {
+        let tumos = Tumos::new(&[Tumo::new(RED, RED), Tumo::new(YELLOW, GREEN)]);
+        PlayerState::new(board, tumos, 0, 0, 0, ojama_fixed, ojama_incoming, 0)
+    }
+
+    fn pick(player_state: &PlayerState) -> Option<Decision> {
+        let chains = enumerate_fireable_chains(player_state);
+        Taiou::pick_chain(player_state, None, None, &chains)
+    }
+
+    #[test]
+    fn offset() {
+        // 2 chain sending 6 ojamas
+        let board = Board::from(concat!(
+            "....B.", // 4
+            "....RB", // 3
+            "....RB", // 2
+            "....RB", // 1
+        ));
+
+        assert!(pick(&player_state(board.clone(), 0, 0)).is_none());
+        assert!(pick(&player_state(board.clone(), 5, 0)).is_some());
+        assert!(pick(&player_state(board.clone(), 0, 5)).is_some());
+        // less than a row
+        assert!(pick(&player_state(Board::new(), 5, 0)).is_none());
+        // not lethal, so keep the chain
+        assert!(pick(&player_state(board, 30, 0)).is_none());
+    }
+
+    #[test]
+    fn offset_partially_if_lethal() {
+        let board = Board::from(concat!(
+            "..Y...", // 7
+            "..G...", // 6
+            "..Y...", // 5
+            "..G.B.", // 4
+            "..Y.RB", // 3
+            "..G.RB", // 2
+            "..Y.RB", // 1
+        ));
+
+        // 4 rows are not lethal
+        assert!(pick(&player_state(board.clone(), 24, 0)).is_none());
+        // 5 rows are lethal
+        assert!(pick(&player_state(board, 30, 0)).is_some());
+    }
+
+    #[test]
+    fn too_late_to_offset() {
+        // 2 chain with the 3rd tumo
+        let board = Board::from(concat!(
+            "....B.", // 4
+            "....RB", // 3
+            "....RB", // 2
+            "....RB", // 1
+        ));
+        let tumos = Tumos::new(&[
+            Tumo::new(YELLOW, GREEN),
+            Tumo::new(YELLOW, GREEN),
+            Tumo::new(RED, RED),
+        ]);
+        let player_state_1p = PlayerState::new(board, tumos, 0, 0, 0, 0, 5, 0);
+        let chains = enumerate_fireable_chains(&player_state_1p);
+
+        // opponent is firing the last chain
+        let board_2p = Board::from(concat!(
+            "GGGG..", // 1
+        ));
+        let player_state_2p = PlayerState::new(board_2p, Tumos::default(), 0, 0, 0, 0, 0, 3);
+        let opponent = OpponentAnalysis::new(&player_state_2p);
+
+        assert!(Taiou::pick_chain(&player_state_1p, None, None, &chains).is_some());
+        assert!(Taiou::pick_chain(
+            &player_state_1p,
+            Some(&player_state_2p),
+            Some(&opponent),
+            &chains
+        )
+        .is_none());
+    }
+}
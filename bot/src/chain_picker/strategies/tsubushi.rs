@@ -0,0 +1,154 @@
+use core::{
+    board::{Board, HEIGHT, WIDTH},
+    player_state::PlayerState,
+};
+
+use crate::{chain_picker::ChainPicker, decision::Decision, OpponentAnalysis};
+
+/// Only small chains are used so that the main chain is kept.
+const MAX_CHAIN: u32 = 3;
+/// Opponent is crippled if less empty cells than this are left.
+const CRIPPLED_EMPTY_CELLS: usize = 4 * WIDTH;
+
+/// Fire a small, fast chain when the ojama kills or cripples the opponent.
+pub struct Tsubushi;
+
+impl ChainPicker for Tsubushi {
+    fn pick_chain(
+        player_state_1p: &PlayerState,
+        player_state_2p: Option<&PlayerState>,
+        opponent: Option<&OpponentAnalysis>,
+        chains: &[Decision],
+    ) -> Option<Decision> {
+        let (player_state_2p, opponent) = player_state_2p.zip(opponent)?;
+        let small_chains: Vec<&Decision> = chains
+            .iter()
+            .filter(|d| d.chain.chain() <= MAX_CHAIN)
+            .collect();
+        if small_chains.is_empty() {
+            return None;
+        }
+
+        let pending = player_state_2p.ojama_pending();
+        if is_crushed(&player_state_2p.board, pending as usize) {
+            // no need to fire
+            return None;
+        }
+
+        // chains are sorted by frame, so the fastest one is taken
+        small_chains
+            .into_iter()
+            .find(|d| {
+                let ojama = player_state_1p.clone().send_ojama(d.chain.score());
+                // opponent can offset only with chains fired before ours ends
+                let counter = opponent.max_ojama_within(d.chain.frame());
+                let ojama = (pending + ojama).saturating_sub(counter);
+
+                is_crushed(&player_state_2p.board, ojama as usize)
+            })
+            .cloned()
+    }
+}
+
+/// True if `ojama` dropped on `board` kills or cripples the player.
+fn is_crushed(board: &Board, ojama: usize) -> bool {
+    let mut board = board.clone();
+    board.drop_ojama_rows(ojama);
+
+    let empty_cells: usize = board.height_array()[1..=WIDTH]
+        .iter()
+        .map(|&h| HEIGHT.saturating_sub(h))
+        .sum();
+
+    board.is_dead() || empty_cells < CRIPPLED_EMPTY_CELLS
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{
+        color::PuyoColor::*,
+        tumo::{Tumo, Tumos},
+    };
+
+    use super::*;
+    use crate::chain_picker::enumerate_fireable_chains;
+
+    fn pick(
+        player_state_1p: &PlayerState,
+        player_state_2p: &PlayerState,
+        chains: &[Decision],
+    ) -> Option<Decision> {
+        let opponent = OpponentAnalysis::new(player_state_2p);
+        Tsubushi::pick_chain(
+            player_state_1p,
+            Some(player_state_2p),
+            Some(&opponent),
+            chains,
+        )
+    }
+
+    fn player_state_1p() -> PlayerState {
+        // 2 chain sending 6 ojamas
+        let board = Board::from(concat!(
+            ".B....", // 4
+            "BR....", // 3
+            "BR....", // 2
+            "BR....", // 1
+        ));
+        let tumos = Tumos::new(&[Tumo::new(RED, RED), Tumo::new(YELLOW, GREEN)]);
+        PlayerState::new(board, tumos, 0, 0, 0, 0, 0, 0)
+    }
+
+    fn player_state_2p(board: Board, ojama_fixed: u32) -> PlayerState {
+        let tumos = Tumos::new(&[Tumo::new(RED, RED), Tumo::new(RED, RED)]);
+        PlayerState::new(board, tumos, 0, 0, 0, ojama_fixed, 0, 0)
+    }
+
+    #[test]
+    fn crush_high_opponent() {
+        let player_state_1p = player_state_1p();
+        let chains = enumerate_fireable_chains(&player_state_1p);
+        assert!(!chains.is_empty());
+
+        let high_board = Board::from(concat!(
+            "..Y...", // 10
+            "..G...", // 9
+            "..B...", // 8
+            "..Y...", // 7
+            "..G...", // 6
+            "..B...", // 5
+            "..Y...", // 4
+            "..G...", // 3
+            "..B...", // 2
+            "..Y...", // 1
+        ));
+        // 1 row of pending ojama does not kill, but 2 rows do
+        let high = player_state_2p(high_board.clone(), 6);
+        assert!(pick(&player_state_1p, &high, &chains).is_some());
+        // already dead by pending ojama
+        let dead = player_state_2p(high_board, 12);
+        assert!(pick(&player_state_1p, &dead, &chains).is_none());
+
+        let low = player_state_2p(
+            Board::from(concat!(
+                "..Y...", // 2
+                "..G...", // 1
+            )),
+            6,
+        );
+        assert!(pick(&player_state_1p, &low, &chains).is_none());
+        assert!(Tsubushi::pick_chain(&player_state_1p, None, None, &chains).is_none());
+    }
+
+    #[test]
+    fn is_crushed_by_ojama() {
+        let board = Board::from(concat!(
+            "..G...", // 2
+            "..B...", // 1
+        ));
+
+        assert!(!is_crushed(&board, 5));
+        assert!(!is_crushed(&board, 30));
+        assert!(is_crushed(&board, 60));
+    }
+}
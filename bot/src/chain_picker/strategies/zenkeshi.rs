@@ -0,0 +1,194 @@
+use core::{board::WIDTH, player_state::PlayerState};
+
+use crate::{chain_picker::ChainPicker, decision::Decision, OpponentAnalysis};
+
+/// All-clears are taken only in the early game so that a built chain is not wasted.
+const EARLY_GAME_PUYOS: usize = 4 * WIDTH;
+/// Chains fired to deny the opponent's all-clear should be small to keep the main chain.
+const MAX_DENIAL_CHAIN: u32 = 2;
+
+/// Take an all-clear reachable within the visible tumos, or deny the opponent's one.
+pub struct Zenkeshi;
+
+impl ChainPicker for Zenkeshi {
+    fn pick_chain(
+        player_state_1p: &PlayerState,
+        player_state_2p: Option<&PlayerState>,
+        opponent: Option<&OpponentAnalysis>,
+        chains: &[Decision],
+    ) -> Option<Decision> {
+        // the bonus does not stack, so another all-clear is worthless while holding one
+        if !player_state_1p.zenkeshi && is_early_game(player_state_1p) {
+            // chains are sorted by frame, so the fastest one is taken
+            if let Some(decision) = chains.iter().find(|d| takes_zenkeshi(player_state_1p, d)) {
+                return Some(decision.clone());
+            }
+        }
+
+        let (player_state_2p, opponent) = player_state_2p.zip(opponent)?;
+        deny(player_state_1p, player_state_2p, opponent, chains)
+    }
+}
+
+/// Fire a small chain whose ojama lands before the opponent fires the all-clear.
+fn deny(
+    player_state_1p: &PlayerState,
+    player_state_2p: &PlayerState,
+    opponent: &OpponentAnalysis,
+    chains: &[Decision],
+) -> Option<Decision> {
+    // the opponent's board is analysed after the ongoing chain, which cannot be replayed here
+    if player_state_2p.zenkeshi || opponent.ongoing_chain.is_some() {
+        return None;
+    }
+
+    // fixed ojama drops when the opponent places a tumo without firing
+    let deadline = opponent
+        .fireable_chains
+        .iter()
+        .filter_map(|d| zenkeshi_deadline(player_state_2p, d))
+        .min()?;
+
+    chains
+        .iter()
+        .filter(|d| d.placements.len() == 1 && d.chain.chain() <= MAX_DENIAL_CHAIN)
+        .find(|d| {
+            d.chain.frame() <= deadline && player_state_1p.clone().send_ojama(d.chain.score()) > 0
+        })
+        .cloned()
+}
+
+fn is_early_game(player_state: &PlayerState) -> bool {
+    let puyo_count: usize = player_state.board.height_array()[1..=WIDTH].iter().sum();
+    puyo_count <= EARLY_GAME_PUYOS
+}
+
+/// True if `decision` leaves the board of `player_state` empty.
+fn takes_zenkeshi(player_state: &PlayerState, decision: &Decision) -> bool {
+    zenkeshi_deadline(player_state, decision).is_some()
+}
+
+/// Frames until the last tumo before the all-clear is placed, or None if `decision` does not
+/// leave the board of `player_state` empty.
+fn zenkeshi_deadline(player_state: &PlayerState, decision: &Decision) -> Option<u32> {
+    // the flag is set if the board gets empty at any point of the decision
+    if !decision.chain.zenkeshi() {
+        return None;
+    }
+
+    let mut board = player_state.board.clone();
+    let mut deadline = 0;
+    let mut frame = 0;
+    for (i, placement) in decision.placements.iter().enumerate() {
+        deadline = frame;
+        let (place_frame, _) = board.place_tumo(&player_state.tumos[i], placement)?;
+        frame += place_frame + board.simulate().frame();
+    }
+
+    board.is_zenkeshi().then_some(deadline)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{
+        board::Board,
+        color::PuyoColor::*,
+        tumo::{Tumo, Tumos},
+    };
+
+    use super::*;
+    use crate::chain_picker::enumerate_fireable_chains;
+
+    #[test]
+    fn take_zenkeshi() {
+        let board = Board::from(concat!(
+            "RR....", // 1
+        ));
+        let tumos = Tumos::new(&[Tumo::new(RED, RED), Tumo::new(YELLOW, GREEN)]);
+        let mut player_state = PlayerState::new(board, tumos, 0, 0, 0, 0, 0, 0);
+        let chains = enumerate_fireable_chains(&player_state);
+
+        let decision = Zenkeshi::pick_chain(&player_state, None, None, &chains).unwrap();
+        assert!(takes_zenkeshi(&player_state, &decision));
+
+        player_state.zenkeshi = true;
+        assert!(Zenkeshi::pick_chain(&player_state, None, None, &chains).is_none());
+    }
+
+    #[test]
+    fn take_zenkeshi_scoring_less() {
+        let board = Board::from(concat!(
+            "G.....", // 4
+            "GG..Y.", // 3
+            "RBBGY.", // 2
+            "RRBGG.", // 1
+        ));
+        let tumos = Tumos::new(&[
+            Tumo::new(RED, GREEN),
+            Tumo::new(BLUE, YELLOW),
+            Tumo::new(YELLOW, GREEN),
+        ]);
+        let player_state = PlayerState::new(board, tumos, 0, 0, 0, 0, 0, 0);
+        let chains = enumerate_fireable_chains(&player_state);
+
+        let decision = Zenkeshi::pick_chain(&player_state, None, None, &chains).unwrap();
+        assert!(takes_zenkeshi(&player_state, &decision));
+        // a faster chain without the all-clear scores more
+        assert!(chains.iter().any(|d| !d.chain.zenkeshi()
+            && d.chain.frame() < decision.chain.frame()
+            && d.chain.score() > decision.chain.score()));
+    }
+
+    #[test]
+    fn deny_zenkeshi() {
+        // 1 chain sending 1 ojama, which is fast since the tumo drops a little
+        let board_1p = Board::from(concat!(
+            "G..RRR", // 8
+            "oooooo", // 7
+            "oooooo", // 6
+            "oooooo", // 5
+            "oooooo", // 4
+            "oooooo", // 3
+            "oooooo", // 2
+            "oooooo", // 1
+        ));
+        let tumos_1p = Tumos::new(&[Tumo::new(RED, RED), Tumo::new(BLUE, GREEN)]);
+        let player_state_1p = PlayerState::new(board_1p, tumos_1p, 0, 0, 0, 0, 0, 0);
+        let chains = enumerate_fireable_chains(&player_state_1p);
+
+        let pick = |board_2p: Board, tumos_2p: Tumos| {
+            let player_state_2p = PlayerState::new(board_2p, tumos_2p, 0, 0, 0, 0, 0, 0);
+            let opponent = OpponentAnalysis::new(&player_state_2p);
+            Zenkeshi::pick_chain(
+                &player_state_1p,
+                Some(&player_state_2p),
+                Some(&opponent),
+                &chains,
+            )
+        };
+
+        // the opponent takes the all-clear with the 3rd tumo
+        let decision = pick(
+            Board::from(concat!(
+                "BB....", // 1
+            )),
+            Tumos::new(&[
+                Tumo::new(YELLOW, YELLOW),
+                Tumo::new(BLUE, YELLOW),
+                Tumo::new(BLUE, YELLOW),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(decision.chain.chain(), 1);
+
+        // too late to deny
+        assert!(pick(
+            Board::from(concat!(
+                "YYY...", // 2
+                "BBB...", // 1
+            )),
+            Tumos::new(&[Tumo::new(BLUE, YELLOW), Tumo::new(RED, GREEN)]),
+        )
+        .is_none());
+    }
+}
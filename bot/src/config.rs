@@ -0,0 +1,211 @@
+use std::{env, fs, path::Path, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    chain_picker::ChainPickerKind,
+    evaluator::EvaluatorSet,
+    searcher::{
+        BeamSearcher, ChokudaiSearcher, DfsSearcher, ExpectimaxSearcher, MonteCarloBeamSearcher,
+        RandomSearcher, Searcher,
+    },
+    OpeningBook, WorkerPool,
+};
+
+/// How [crate::Nova] thinks, which can be loaded from a TOML file.
+///
+/// ```toml
+/// chain_pickers = ["houwa", "taiou", "tsubushi", "zenkeshi"]
+/// threads = 4
+/// visible_tumos = 3
+///
+/// [searcher]
+/// kind = "monte_carlo_beam"
+/// samples = 20
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NovaConfig {
+    /// Tried in this order before searching. Only houwa is enabled by default.
+    pub chain_pickers: Vec<ChainPickerKind>,
+    pub searcher: SearcherConfig,
+    /// Same format as the weights file. See [EvaluatorSet::load].
+    pub evaluators: EvaluatorSet,
+    /// Same format as the opening book file. See [OpeningBook::load].
+    pub opening_book: OpeningBook,
+    /// Number of search threads. All available threads are used if None.
+    pub threads: Option<usize>,
+    /// Number of tumos to look at. All given tumos are used if None.
+    pub visible_tumos: Option<usize>,
+}
+
+impl Default for NovaConfig {
+    fn default() -> Self {
+        Self {
+            chain_pickers: vec![ChainPickerKind::Houwa],
+            searcher: SearcherConfig::default(),
+            evaluators: EvaluatorSet::default(),
+            opening_book: OpeningBook::default(),
+            threads: None,
+            visible_tumos: None,
+        }
+    }
+}
+
+impl NovaConfig {
+    pub fn from_toml(s: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(s)
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).unwrap()
+    }
+
+    /// Load from the file given by `--config <path>` of the command line arguments,
+    /// or the default config if not given.
+    /// Evaluators are replaced with the weights file given by `--weights <path>` if any,
+    /// and the opening book with the file given by `--openings <path>`.
+    pub fn from_args() -> Self {
+        let args: Vec<String> = env::args().collect();
+        let path_arg = |name: &str| {
+            let i = args.iter().position(|arg| arg == name)?;
+            let path = args.get(i + 1);
+            Some(path.unwrap_or_else(|| panic!("`{}` requires a path", name)))
+        };
+
+        let mut config = path_arg("--config").map_or_else(Self::default, Self::load);
+        if let Some(path) = path_arg("--weights") {
+            config.evaluators = EvaluatorSet::load(path);
+        }
+        if let Some(path) = path_arg("--openings") {
+            config.opening_book = OpeningBook::load(path);
+        }
+        config
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let s = fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("failed to read {}: {}", path.display(), e));
+        Self::from_toml(&s).unwrap_or_else(|e| panic!("failed to parse {}: {}", path.display(), e))
+    }
+
+    pub fn with_threads(self, threads: usize) -> Self {
+        Self {
+            threads: Some(threads),
+            ..self
+        }
+    }
+
+    pub fn with_evaluators(self, evaluators: EvaluatorSet) -> Self {
+        Self { evaluators, ..self }
+    }
+
+    /// Worker pool with [NovaConfig::threads] threads.
+    pub fn worker_pool(&self) -> WorkerPool {
+        match self.threads {
+            Some(threads) => WorkerPool::new(threads),
+            None => WorkerPool::default(),
+        }
+    }
+}
+
+/// [Searcher] and its parameters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum SearcherConfig {
+    Beam,
+    Chokudai,
+    Dfs,
+    Expectimax,
+    MonteCarloBeam {
+        #[serde(default = "default_samples")]
+        samples: usize,
+    },
+    Random,
+}
+
+fn default_samples() -> usize {
+    MonteCarloBeamSearcher::SAMPLES
+}
+
+impl Default for SearcherConfig {
+    fn default() -> Self {
+        SearcherConfig::MonteCarloBeam {
+            samples: default_samples(),
+        }
+    }
+}
+
+impl SearcherConfig {
+    pub fn build(&self, pool: Arc<WorkerPool>) -> Box<dyn Searcher + Send + Sync> {
+        match *self {
+            SearcherConfig::Beam => Box::new(BeamSearcher),
+            SearcherConfig::Chokudai => Box::new(ChokudaiSearcher),
+            SearcherConfig::Dfs => Box::new(DfsSearcher),
+            SearcherConfig::Expectimax => Box::new(ExpectimaxSearcher),
+            SearcherConfig::MonteCarloBeam { samples } => {
+                Box::new(MonteCarloBeamSearcher::new(pool).with_samples(samples))
+            }
+            SearcherConfig::Random => Box::new(RandomSearcher),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_toml() {
+        let config = NovaConfig::from_toml(concat!(
+            "chain_pickers = []\n",
+            "threads = 2\n",
+            "[searcher]\n",
+            "kind = \"beam\"\n",
+        ))
+        .unwrap();
+
+        assert!(config.chain_pickers.is_empty());
+        assert_eq!(config.searcher, SearcherConfig::Beam);
+        assert_eq!(config.threads, Some(2));
+        assert_eq!(config.visible_tumos, None);
+
+        let config = NovaConfig::from_toml("[searcher]\nkind = \"monte_carlo_beam\"").unwrap();
+        assert_eq!(config.chain_pickers, vec![ChainPickerKind::Houwa]);
+        assert_eq!(config.searcher, SearcherConfig::default());
+
+        let config = NovaConfig::from_toml(
+            "chain_pickers = [\"houwa\", \"taiou\", \"tsubushi\", \"zenkeshi\"]",
+        )
+        .unwrap();
+        assert_eq!(
+            config.chain_pickers,
+            vec![
+                ChainPickerKind::Houwa,
+                ChainPickerKind::Taiou,
+                ChainPickerKind::Tsubushi,
+                ChainPickerKind::Zenkeshi
+            ]
+        );
+
+        assert!(NovaConfig::from_toml("unknown = 1").is_err());
+
+        let config = NovaConfig::from_toml("[evaluators]\nendgame_puyos = 60").unwrap();
+        assert_eq!(config.evaluators.endgame_puyos, 60);
+    }
+
+    #[test]
+    fn to_toml() {
+        let config = NovaConfig {
+            visible_tumos: Some(3),
+            ..NovaConfig::default()
+        };
+        let restored = NovaConfig::from_toml(&config.to_toml()).unwrap();
+
+        assert_eq!(restored.chain_pickers, config.chain_pickers);
+        assert_eq!(restored.searcher, config.searcher);
+        assert_eq!(restored.visible_tumos, Some(3));
+        assert_eq!(restored.evaluators.build.bump, config.evaluators.build.bump);
+    }
+}
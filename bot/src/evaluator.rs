@@ -1,23 +1,29 @@
 use core::{
     board::{Board, WIDTH},
-    chain::Chain,
+    chain::{score, Chain},
     search::ComplementedPuyo,
 };
 
 pub use evaluators::*;
 use feature_extraction::BoardFeature;
+use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::DetailedPlayerState;
 
 mod evaluators;
 mod feature_extraction;
+mod weights;
+
+/// Name of [Evaluator]. (Also keeps serde from borrowing `&'static str` from the input.)
+pub type EvaluatorName = &'static str;
 
 // For SPSA
-pub type EvaluatorOverrider = (&'static str, Evaluator);
+pub type EvaluatorOverrider = (EvaluatorName, Evaluator);
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Evaluator {
-    pub name: &'static str,
+    #[serde(deserialize_with = "deserialize_name")]
+    pub name: EvaluatorName,
     pub bump: i32,
     pub dent: i32,
     pub dead_cells: i32,
@@ -38,6 +44,87 @@ pub struct Evaluator {
     /// Sum of scores of detected chains divided by 1024.
     /// (Using 1024 instead of 1000 (<=> "k") since the division can be done by a simple bit shift.)
     pub detected_score_per_k: i32,
+    /// If set, the weights above are replaced with the ones interpolated by the number of puyos.
+    #[serde(skip)]
+    pub taper: Option<Taper>,
+}
+
+macro_rules! weights {
+    ($($feat:ident),* $(,)?) => {
+        /// Number of weights of [Evaluator].
+        pub const WEIGHTS: usize = [$(stringify!($feat)),*].len();
+
+        impl Evaluator {
+            fn weights(&self) -> [i32; WEIGHTS] {
+                [$(self.$feat),*]
+            }
+
+            fn with_weights(self, weights: [i32; WEIGHTS]) -> Self {
+                let [$($feat),*] = weights;
+                Self { $($feat,)* ..self }
+            }
+        }
+    };
+}
+
+weights![
+    bump,
+    dent,
+    dead_cells,
+    conn_2_v,
+    conn_2_h,
+    ojama,
+    non_u_shape,
+    non_u_shape_sq,
+    frame,
+    frame_by_chain,
+    frame_by_chigiri,
+    detected_need,
+    detected_keys,
+    detected_chain,
+    detected_score_per_k,
+];
+
+/// Anchors of weights, which are linearly interpolated by the number of puyos between them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Taper {
+    /// Number of puyos at each anchor in ascending order.
+    puyos: [usize; 3],
+    weights: [[i32; WEIGHTS]; 3],
+}
+
+impl Taper {
+    pub fn new(anchors: [(usize, Evaluator); 3]) -> Self {
+        debug_assert!(anchors.windows(2).all(|w| w[0].0 <= w[1].0));
+
+        Self {
+            puyos: anchors.map(|(puyos, _)| puyos),
+            weights: anchors.map(|(_, evaluator)| evaluator.weights()),
+        }
+    }
+
+    fn weights_at(&self, puyos: usize) -> [i32; WEIGHTS] {
+        let Some(i) = self.puyos.iter().rposition(|&p| p <= puyos) else {
+            return self.weights[0];
+        };
+        if i + 1 == self.puyos.len() {
+            return self.weights[i];
+        }
+
+        let (from, to) = (self.puyos[i], self.puyos[i + 1]);
+        let t = (puyos - from) as i64;
+        let len = (to - from) as i64;
+        std::array::from_fn(|k| {
+            let (a, b) = (self.weights[i][k] as i64, self.weights[i + 1][k] as i64);
+            (a + (b - a) * t / len) as i32
+        })
+    }
+}
+
+/// Evaluators are loaded only a few times per process, so leaking names is fine.
+fn deserialize_name<'de, D: Deserializer<'de>>(deserializer: D) -> Result<EvaluatorName, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    Ok(Box::leak(name.into_boxed_str()))
 }
 
 impl Evaluator {
@@ -47,40 +134,68 @@ impl Evaluator {
     }
 
     pub fn evaluate(&self, player_state: &DetailedPlayerState) -> i32 {
-        debug_assert!(player_state.board.popping_puyos().is_none());
+        self.evaluate_with_board_score(player_state, self.evaluate_board(&player_state.board))
+    }
+
+    /// Same as [Evaluator::evaluate], but reuse `board_score` calculated by [Evaluator::evaluate_board].
+    pub fn evaluate_with_board_score(
+        &self,
+        player_state: &DetailedPlayerState,
+        board_score: i32,
+    ) -> i32 {
+        if board_score == i32::MIN {
+            return i32::MIN;
+        }
+        if let Some(taper) = &self.taper {
+            return self
+                .tapered(taper, &player_state.board)
+                .evaluate_with_board_score(player_state, board_score);
+        }
 
-        if player_state.board.is_dead() {
+        let mut score = board_score;
+
+        score += self.frame * player_state.frame_since_control_start as i32;
+        score += self.frame_by_chain * player_state.frame_by_chain as i32;
+        score += self.frame_by_chigiri * player_state.frame_by_chigiri as i32;
+
+        score
+    }
+
+    /// Evaluate features which only depend on the board, so that the result can be cached.
+    pub fn evaluate_board(&self, board: &Board) -> i32 {
+        debug_assert!(board.popping_puyos().is_none());
+
+        if board.is_dead() {
             return i32::MIN;
         }
+        if let Some(taper) = &self.taper {
+            return self.tapered(taper, board).evaluate_board(board);
+        }
 
         let mut score = 0i32;
 
         for x in 1..=WIDTH {
-            let bump = player_state.board.bump(x);
-            let dent = player_state.board.dent(x);
+            let bump = board.bump(x);
+            let dent = board.dent(x);
             score += self.bump * (bump * bump);
             score += self.dent * (dent * dent);
         }
 
-        score += self.dead_cells * player_state.board.dead_cells();
+        score += self.dead_cells * board.dead_cells();
 
-        let (conn_2_v, conn_2_h, conn_3) = player_state.board.connectivity();
+        let (conn_2_v, conn_2_h, conn_3) = board.connectivity();
         score += self.conn_2_v * conn_2_v;
         score += self.conn_2_h * conn_2_h;
         score += self.conn_3() * conn_3;
 
-        score += self.ojama * player_state.board.ojama_count();
+        score += self.ojama * board.ojama_count();
 
-        let (non_u_shape, non_u_shape_sq) = player_state.board.non_u_shape();
+        let (non_u_shape, non_u_shape_sq) = board.non_u_shape();
         score += self.non_u_shape * non_u_shape;
         score += self.non_u_shape_sq * non_u_shape_sq;
 
-        score += self.frame * player_state.frame_since_control_start as i32;
-        score += self.frame_by_chain * player_state.frame_by_chain as i32;
-        score += self.frame_by_chigiri * player_state.frame_by_chigiri as i32;
-
         let mut detected_score = i32::MIN;
-        player_state.board.detect_potential_chain(
+        board.detect_potential_chain(
             3,
             1,
             |_board: Board, fire_x: usize, cp: ComplementedPuyo, chain: Chain| {
@@ -105,6 +220,67 @@ impl Evaluator {
         score
     }
 
+    /// Upper bound of [Evaluator::evaluate] for any state reached from `player_state` with at most
+    /// `puyos` puyos on the board, or None if some penalty could also be a reward.
+    pub fn upper_bound(&self, player_state: &DetailedPlayerState, puyos: usize) -> Option<i32> {
+        // interpolated weights never exceed the largest anchor
+        let weights = match &self.taper {
+            Some(taper) => {
+                std::array::from_fn(|k| taper.weights.iter().map(|w| w[k]).max().unwrap())
+            }
+            None => self.weights(),
+        };
+        let evaluator = Self {
+            taper: None,
+            ..*self
+        }
+        .with_weights(weights);
+
+        // frames only increase after `player_state`, so their part is bounded by the current one
+        let penalties = [
+            evaluator.bump,
+            evaluator.dent,
+            evaluator.dead_cells,
+            evaluator.ojama,
+            evaluator.non_u_shape,
+            evaluator.non_u_shape_sq,
+            evaluator.frame,
+            evaluator.frame_by_chain,
+            evaluator.frame_by_chigiri,
+            evaluator.detected_need,
+            evaluator.detected_keys,
+        ];
+        if penalties.iter().any(|&w| w > 0) {
+            return None;
+        }
+
+        // each puyo is counted at most once for each connectivity
+        let conn = evaluator.conn_2_v.max(0) + evaluator.conn_2_h.max(0) + evaluator.conn_3();
+        let mut bound = conn * puyos as i32;
+
+        // detected chains pop the puyos and at most 3 complemented ones,
+        // and the connection bonus never exceeds the number of popped puyos
+        let popped = puyos + 3;
+        let chain = (popped / 4).min(19);
+        let score = 10
+            * popped as u32
+            * (score::chain_bonus(chain) + popped as u32 + score::color_bonus(4));
+        bound += evaluator.detected_chain.max(0) * chain as i32;
+        bound += evaluator.detected_score_per_k.max(0) * (score >> 10) as i32;
+
+        Some(evaluator.evaluate_with_board_score(player_state, bound))
+    }
+
+    /// Evaluator with the weights of `taper` for `board`.
+    fn tapered(&self, taper: &Taper, board: &Board) -> Self {
+        let puyos = board.height_array()[1..=WIDTH].iter().sum();
+        Self {
+            taper: None,
+            ..*self
+        }
+        .with_weights(taper.weights_at(puyos))
+    }
+
     const fn zero() -> Self {
         Self {
             name: "noname",
@@ -126,6 +302,7 @@ impl Evaluator {
             detected_keys: 0,
             detected_chain: 0,
             detected_score_per_k: 0,
+            taper: None,
         }
     }
 }
@@ -1,35 +1,117 @@
-use core::{board::WIDTH, player_state::PlayerState};
+use core::{
+    board::{HEIGHT, WIDTH},
+    player_state::PlayerState,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{Evaluator, EvaluatorOverrider, Taper};
+use crate::OpponentAnalysis;
+
+/// Evaluators to be selected depending on the situation.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EvaluatorSet {
+    /// Use `build_midgame` if there are at least this many puyos on the board.
+    pub midgame_puyos: usize,
+    /// Use `build_endgame` if there are at least this many puyos on the board.
+    pub endgame_puyos: usize,
+    /// Interpolate `build` (at 0 puyos), `build_midgame` (at `midgame_puyos`) and
+    /// `build_endgame` (at `endgame_puyos`) instead of switching between them.
+    pub taper: bool,
+    pub build: Evaluator,
+    pub build_midgame: Evaluator,
+    pub build_endgame: Evaluator,
+    pub zenkeshi: Evaluator,
+}
 
-use super::Evaluator;
+impl EvaluatorSet {
+    /// `opponent` is the analysis of `player_state_2p`.
+    pub fn select(
+        &self,
+        player_state_1p: &PlayerState,
+        player_state_2p: Option<&PlayerState>,
+        opponent: Option<&OpponentAnalysis>,
+    ) -> Evaluator {
+        let Some(player_state_2p) = player_state_2p else {
+            return self.select_build(player_state_1p);
+        };
 
-pub fn select_best_evaluator(
-    player_state_1p: &PlayerState,
-    player_state_2p: Option<&PlayerState>,
-) -> Evaluator {
-    let player_state_2p = match player_state_2p {
-        Some(state) => state,
-        None => return select_best_build_evaluator(player_state_1p),
-    };
+        // prepare a chain to answer with if the opponent can already kill us,
+        // since the endgame evaluator weights detected chains the most
+        if let Some(opponent) = opponent {
+            let height_3 = player_state_1p.board.height_array()[3];
+            let ojama_to_kill = (HEIGHT.saturating_sub(height_3) * WIDTH) as u32;
+            let ojama = player_state_1p.ojama_pending() + opponent.max_ojama_within(u32::MAX);
+            if ojama >= ojama_to_kill {
+                return self.build_endgame;
+            }
+        }
 
-    if player_state_1p.carry_over >= 70 * 30 && player_state_2p.carry_over >= 70 * 30 {
-        return ZENKESHI;
+        // both bonuses offset each other, so the race is on who fires first
+        if player_state_1p.zenkeshi && player_state_2p.zenkeshi {
+            return self.zenkeshi;
+        }
+
+        self.select_build(player_state_1p)
     }
 
-    select_best_build_evaluator(player_state_1p)
+    pub fn select_build(&self, player_state: &PlayerState) -> Evaluator {
+        if self.taper {
+            return self.tapered();
+        }
+
+        let puyo_count = player_state.board.height_array()[1..=WIDTH]
+            .iter()
+            .sum::<usize>();
+
+        // TODO: we may want to use different evaluator if there's many ojamas?
+        if puyo_count >= self.endgame_puyos {
+            self.build_endgame
+        } else if puyo_count >= self.midgame_puyos {
+            self.build_midgame
+        } else {
+            self.build
+        }
+    }
+
+    /// Evaluator interpolating the build evaluators by the number of puyos. See [EvaluatorSet::taper].
+    pub fn tapered(&self) -> Evaluator {
+        Evaluator {
+            name: "tapered",
+            taper: Some(Taper::new([
+                (0, self.build),
+                (self.midgame_puyos, self.build_midgame),
+                (self.endgame_puyos, self.build_endgame),
+            ])),
+            ..self.build
+        }
+    }
+
+    /// Replace the evaluator whose name is the same as the overrider.
+    pub fn with_overrider(self, (name, evaluator): EvaluatorOverrider) -> Self {
+        let replace = |e: Evaluator| if e.name == name { evaluator } else { e };
+        Self {
+            build: replace(self.build),
+            build_midgame: replace(self.build_midgame),
+            build_endgame: replace(self.build_endgame),
+            zenkeshi: replace(self.zenkeshi),
+            ..self
+        }
+    }
 }
 
-pub fn select_best_build_evaluator(player_state: &PlayerState) -> Evaluator {
-    let puyo_count = player_state.board.height_array()[1..=WIDTH]
-        .iter()
-        .sum::<usize>();
-
-    // TODO: we may want to use different evaluator if there's many ojamas?
-    if puyo_count >= 9 * WIDTH {
-        BUILD_ENDGAME
-    } else if puyo_count >= 5 * WIDTH {
-        BUILD_MIDGAME
-    } else {
-        BUILD
+impl Default for EvaluatorSet {
+    fn default() -> Self {
+        Self {
+            midgame_puyos: 5 * WIDTH,
+            endgame_puyos: 9 * WIDTH,
+            taper: false,
+            build: BUILD,
+            build_midgame: BUILD_MIDGAME,
+            build_endgame: BUILD_ENDGAME,
+            zenkeshi: ZENKESHI,
+        }
     }
 }
 
@@ -53,6 +135,7 @@ pub const BUILD: Evaluator = Evaluator {
     detected_keys: 0,
     detected_chain: 182,
     detected_score_per_k: 90,
+    taper: None,
 };
 
 pub const BUILD_MIDGAME: Evaluator = Evaluator {
@@ -88,12 +171,109 @@ pub const HURRY: Evaluator = Evaluator {
     name: "hurry",
     ..Evaluator::zero()
 };
+/// Selected when both players hold an all-clear, whose bonuses offset each other.
+/// Tuned by `spsa --zenkeshi`, which plays 2p games where both players start with an all-clear.
 pub const ZENKESHI: Evaluator = Evaluator {
     name: "zenkeshi",
-    ..Evaluator::zero()
+    bump: -60,
+    dent: -170,
+    frame: -1,
+    frame_by_chain: 0,
+    detected_need: -66,
+    detected_keys: 0,
+    detected_chain: 141,
+    detected_score_per_k: 59,
+    ..BUILD
 };
 #[allow(dead_code)]
 pub const TSUBUSHI: Evaluator = Evaluator {
     name: "tsubushi",
     ..Evaluator::zero()
 };
+
+#[cfg(test)]
+mod tests {
+    use core::{board::Board, tumo::Tumos};
+
+    use super::*;
+    use crate::DetailedPlayerState;
+
+    #[test]
+    fn select_with_overrider() {
+        let player_state = PlayerState::new(Board::new(), Tumos::default(), 0, 0, 0, 0, 0, 0);
+        let overrided = Evaluator { bump: 0, ..BUILD };
+        let evaluators = EvaluatorSet::default().with_overrider((BUILD.name, overrided));
+
+        assert_eq!(evaluators.select(&player_state, None, None).bump, 0);
+        assert_eq!(evaluators.build_midgame.bump, BUILD_MIDGAME.bump);
+    }
+
+    #[test]
+    fn tapered() {
+        let evaluators = EvaluatorSet {
+            taper: true,
+            ..EvaluatorSet::default()
+        };
+        let evaluator = evaluators.select_build(&PlayerState::default());
+        assert_eq!(evaluator.name, "tapered");
+
+        let evaluate = |board: &Board, evaluator: &Evaluator| {
+            let player_state: DetailedPlayerState = PlayerState {
+                board: board.clone(),
+                ..PlayerState::default()
+            }
+            .into();
+            evaluator.evaluate(&player_state)
+        };
+
+        // same as the anchors at the anchors
+        let empty = Board::new();
+        assert_eq!(evaluate(&empty, &evaluator), evaluate(&empty, &BUILD));
+        let midgame: Board = [5, 5, 5, 5, 5, 5].into();
+        assert_eq!(
+            evaluate(&midgame, &evaluator),
+            evaluate(&midgame, &BUILD_MIDGAME)
+        );
+        let endgame: Board = [10, 10, 9, 9, 10, 10].into();
+        assert_eq!(
+            evaluate(&endgame, &evaluator),
+            evaluate(&endgame, &BUILD_ENDGAME)
+        );
+
+        // between the anchors
+        let board: Board = [7, 7, 7, 7, 7, 7].into();
+        let (mid, end) = (
+            evaluate(&board, &BUILD_MIDGAME),
+            evaluate(&board, &BUILD_ENDGAME),
+        );
+        let score = evaluate(&board, &evaluator);
+        assert!(mid.min(end) <= score && score <= mid.max(end));
+    }
+
+    #[test]
+    fn select_zenkeshi() {
+        let mut player_state_1p =
+            PlayerState::new(Board::new(), Tumos::default(), 0, 0, 0, 0, 0, 0);
+        let mut player_state_2p = player_state_1p.clone();
+        let evaluators = EvaluatorSet::default();
+
+        let select = |player_state_1p: &PlayerState, player_state_2p: &PlayerState| {
+            evaluators
+                .select(player_state_1p, Some(player_state_2p), None)
+                .name
+        };
+
+        assert_eq!(select(&player_state_1p, &player_state_2p), BUILD.name);
+        // only when both players hold an all-clear
+        player_state_1p.zenkeshi = true;
+        assert_eq!(select(&player_state_1p, &player_state_2p), BUILD.name);
+        player_state_2p.zenkeshi = true;
+        assert_eq!(select(&player_state_1p, &player_state_2p), ZENKESHI.name);
+
+        // answering a lethal threat comes first
+        player_state_1p.ojama_fixed = (HEIGHT * WIDTH) as u32;
+        let opponent = OpponentAnalysis::new(&player_state_2p);
+        let selected = evaluators.select(&player_state_1p, Some(&player_state_2p), Some(&opponent));
+        assert_eq!(selected.name, BUILD_ENDGAME.name);
+    }
+}
@@ -0,0 +1,107 @@
+use std::{fs, path::Path};
+
+use super::EvaluatorSet;
+
+impl EvaluatorSet {
+    pub fn from_toml(s: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(s)
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).unwrap()
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Load weights from a JSON file if the extension is `.json`, or a TOML file otherwise.
+    /// Evaluators missing in the file are the default ones.
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let s = fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("failed to read {}: {}", path.display(), e));
+
+        let evaluators = if is_json(path) {
+            Self::from_json(&s).map_err(|e| e.to_string())
+        } else {
+            Self::from_toml(&s).map_err(|e| e.to_string())
+        };
+        evaluators.unwrap_or_else(|e| panic!("failed to parse {}: {}", path.display(), e))
+    }
+
+    /// Save weights in the same format as [EvaluatorSet::load].
+    pub fn save(&self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        let s = if is_json(path) {
+            self.to_json()
+        } else {
+            self.to_toml()
+        };
+
+        fs::write(path, s).unwrap_or_else(|e| panic!("failed to write {}: {}", path.display(), e));
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluator::{Evaluator, BUILD, BUILD_ENDGAME, ZENKESHI};
+
+    #[test]
+    fn to_from_toml() {
+        let evaluators = EvaluatorSet {
+            endgame_puyos: 50,
+            ..EvaluatorSet::default()
+        }
+        .with_overrider((BUILD.name, Evaluator { bump: -1, ..BUILD }));
+        let restored = EvaluatorSet::from_toml(&evaluators.to_toml()).unwrap();
+
+        assert_eq!(restored.endgame_puyos, 50);
+        assert_eq!(restored.build.name, "build");
+        assert_eq!(restored.build.bump, -1);
+        assert_eq!(restored.build_endgame.dent, BUILD_ENDGAME.dent);
+    }
+
+    #[test]
+    fn to_from_json() {
+        let evaluators = EvaluatorSet::default();
+        let restored = EvaluatorSet::from_json(&evaluators.to_json()).unwrap();
+
+        assert_eq!(restored.midgame_puyos, evaluators.midgame_puyos);
+        assert_eq!(restored.build_midgame.name, "build_mid");
+        assert_eq!(restored.zenkeshi.bump, ZENKESHI.bump);
+    }
+
+    #[test]
+    fn missing_evaluators_are_default() {
+        let restored = EvaluatorSet::from_toml("endgame_puyos = 60").unwrap();
+
+        assert_eq!(restored.endgame_puyos, 60);
+        assert_eq!(restored.build.detected_chain, BUILD.detected_chain);
+    }
+
+    #[test]
+    fn save_and_load() {
+        let dir = std::env::temp_dir();
+        let evaluators = EvaluatorSet {
+            midgame_puyos: 20,
+            ..EvaluatorSet::default()
+        };
+
+        for file in ["nova_weights_test.toml", "nova_weights_test.json"] {
+            let path = dir.join(file);
+            evaluators.save(&path);
+            assert_eq!(EvaluatorSet::load(&path).midgame_puyos, 20);
+            fs::remove_file(&path).unwrap();
+        }
+    }
+}
@@ -1,12 +1,20 @@
+mod config;
 mod decision;
 mod detailed_player_state;
 mod nova;
+mod opening_book;
+mod opponent_analysis;
+mod worker_pool;
 
 // set to pub for benchmark
 pub mod chain_picker;
 pub mod evaluator;
 pub mod searcher;
 
+pub use config::{NovaConfig, SearcherConfig};
 pub use decision::DecisionWithElapsed;
 pub use detailed_player_state::DetailedPlayerState;
 pub use nova::Nova;
+pub use opening_book::{tumo_pattern, Opening, OpeningBook};
+pub use opponent_analysis::OpponentAnalysis;
+pub use worker_pool::WorkerPool;
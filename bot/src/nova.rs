@@ -1,75 +1,160 @@
 use core::player_state::PlayerState;
-use std::time::Instant;
+use std::{sync::Arc, time::Instant};
 
 use log::warn;
 
 use crate::{
-    chain_picker::{enumerate_fireable_chains, strategies::*, ChainPicker},
+    chain_picker::enumerate_fireable_chains,
     decision::{Decision, DecisionWithElapsed},
-    evaluator::{select_best_evaluator, EvaluatorOverrider},
+    evaluator::EvaluatorOverrider,
     searcher::*,
+    NovaConfig, OpponentAnalysis, WorkerPool,
 };
 
-#[derive(Default)]
 pub struct Nova {
-    evaluator_overrider: Option<EvaluatorOverrider>,
+    config: NovaConfig,
+    searcher: Box<dyn Searcher + Send + Sync>,
 }
 
 impl Nova {
-    pub fn with_evaluator_overrider(overrider: EvaluatorOverrider) -> Self {
+    /// Nova thinking as `config`. Search threads are kept alive until Nova is dropped.
+    pub fn new(config: NovaConfig) -> Self {
+        let pool = Arc::new(config.worker_pool());
+        Self::with_pool(config, pool)
+    }
+
+    /// Nova sharing `pool` with others. [NovaConfig::threads] is ignored.
+    pub fn with_pool(config: NovaConfig, pool: Arc<WorkerPool>) -> Self {
+        Self {
+            searcher: config.searcher.build(pool),
+            config,
+        }
+    }
+
+    pub fn with_evaluator_overrider(self, overrider: EvaluatorOverrider) -> Self {
         Self {
-            evaluator_overrider: Some(overrider),
+            config: NovaConfig {
+                evaluators: self.config.evaluators.with_overrider(overrider),
+                ..self.config
+            },
+            ..self
         }
     }
 
+    pub fn config(&self) -> &NovaConfig {
+        &self.config
+    }
+
     pub fn think(
         &self,
         player_state_1p: &PlayerState,
         player_state_2p: Option<&PlayerState>,
         think_frame: Option<u32>,
+    ) -> DecisionWithElapsed {
+        let limit = SearchLimit::from_think_frame(Instant::now(), think_frame);
+        self.think_with_limit(player_state_1p, player_state_2p, &limit)
+    }
+
+    /// Same as [Nova::think], but stop thinking at the deadline or when the stop flag of `limit` is set.
+    pub fn think_with_limit(
+        &self,
+        player_state_1p: &PlayerState,
+        player_state_2p: Option<&PlayerState>,
+        limit: &SearchLimit,
     ) -> DecisionWithElapsed {
         let start = Instant::now();
-        let decision = self
-            .think_internal(player_state_1p, player_state_2p, think_frame)
-            .with_elapsed(start.elapsed());
+        let decision = self.think_internal(player_state_1p, player_state_2p, limit);
+        let decision = Self::avoid_death(player_state_1p, decision);
 
         if decision.placements.is_empty() {
             warn!("Nova returned Decision with empty Placement!");
             return Decision::fallback().with_elapsed(start.elapsed());
         }
 
-        decision
+        decision.with_elapsed(start.elapsed())
+    }
+
+    /// Replace `decision` with the survival one if its first placement kills Nova.
+    /// `decision` is kept if every placement is fatal.
+    fn avoid_death(player_state_1p: &PlayerState, decision: Decision) -> Decision {
+        if decision
+            .placements
+            .first()
+            .is_some_and(|placement| !SurvivalSearcher::is_fatal(player_state_1p, placement))
+        {
+            return decision;
+        }
+
+        match SurvivalSearcher::survival_decision(player_state_1p) {
+            Some(survival) => {
+                warn!("Nova avoided a fatal placement");
+                survival
+            }
+            None => decision,
+        }
     }
 
     fn think_internal(
         &self,
         player_state_1p: &PlayerState,
         player_state_2p: Option<&PlayerState>,
-        think_frame: Option<u32>,
+        limit: &SearchLimit,
     ) -> Decision {
-        // TODO: OpeningMatcher
+        let visible_player_state_1p;
+        let player_state_1p = match self.config.visible_tumos {
+            Some(visible) => {
+                visible_player_state_1p = PlayerState {
+                    tumos: player_state_1p.tumos.slice_visible_tumos(visible, None),
+                    ..player_state_1p.clone()
+                };
+                &visible_player_state_1p
+            }
+            None => player_state_1p,
+        };
 
-        let chain_decisions = enumerate_fireable_chains(player_state_1p);
-        macro_rules! try_pick_chain {
-            ($($chain_picker:ty),*) => {
-                $(
-                    if let Some(decision) = <$chain_picker>::pick_chain(player_state_1p, player_state_2p, &chain_decisions) {
-                        return decision;
-                    }
-                )*
-            };
+        if let Some(decision) = self.config.opening_book.pick(player_state_1p) {
+            return decision;
         }
-        try_pick_chain!(Houwa);
 
-        let mut evaluator = select_best_evaluator(player_state_1p, player_state_2p);
-        if let Some((expr_name, expr_evaluator)) = self.evaluator_overrider {
-            if evaluator.name == expr_name {
-                evaluator = expr_evaluator;
+        let opponent = player_state_2p.map(OpponentAnalysis::new);
+
+        // ojama of the opponent's ongoing chain is going to come
+        let forecasted_player_state_1p;
+        let player_state_1p = match opponent.as_ref().map_or(0, |o| o.ongoing_ojama()) {
+            0 => player_state_1p,
+            ojama => {
+                forecasted_player_state_1p = PlayerState {
+                    ojama_incoming: player_state_1p.ojama_incoming + ojama,
+                    ..player_state_1p.clone()
+                };
+                &forecasted_player_state_1p
+            }
+        };
+
+        let chain_decisions = enumerate_fireable_chains(player_state_1p);
+        for chain_picker in &self.config.chain_pickers {
+            if let Some(decision) = chain_picker.pick_chain(
+                player_state_1p,
+                player_state_2p,
+                opponent.as_ref(),
+                &chain_decisions,
+            ) {
+                return decision;
             }
         }
-        let build_decision =
-            MonteCarloBeamSearcher::search(player_state_1p, &evaluator, think_frame);
+
+        let evaluator =
+            self.config
+                .evaluators
+                .select(player_state_1p, player_state_2p, opponent.as_ref());
+        let build_decision = self.searcher.search(player_state_1p, &evaluator, limit);
 
         build_decision
     }
 }
+
+impl Default for Nova {
+    fn default() -> Self {
+        Self::new(NovaConfig::default())
+    }
+}
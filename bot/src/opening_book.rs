@@ -0,0 +1,387 @@
+use core::{
+    board::{Board, WIDTH},
+    chain::Chain,
+    color::PuyoColor,
+    placement::Placement,
+    player_state::PlayerState,
+    tumo::Tumo,
+};
+use std::{fs, path::Path};
+
+use itertools::Itertools;
+use serde::{de::Error, Deserialize, Serialize};
+
+use crate::decision::Decision;
+
+/// The book used unless another one is given. Users can copy and edit this file.
+const DEFAULT_BOOK: &str = include_str!("../openings.toml");
+const LETTERS: [char; 4] = ['A', 'B', 'C', 'D'];
+
+/// Opening sequences played from the first tumo while the board matches them.
+/// See `bot/openings.toml` for the format.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OpeningBook {
+    /// Earlier openings take priority.
+    #[serde(default = "default_openings")]
+    pub openings: Vec<Opening>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Opening {
+    pub name: String,
+    /// Pairs of letters standing for distinct colors (e.g. "AA AB CA").
+    pub tumos: String,
+    /// `[axis_x, rot]` for each pair.
+    pub placements: Vec<(usize, usize)>,
+}
+
+impl Default for OpeningBook {
+    fn default() -> Self {
+        Self::from_toml(DEFAULT_BOOK).expect("the default opening book is broken")
+    }
+}
+
+fn default_openings() -> Vec<Opening> {
+    OpeningBook::default().openings
+}
+
+impl OpeningBook {
+    pub fn from_toml(s: &str) -> Result<Self, toml::de::Error> {
+        let book: Self = toml::from_str(s)?;
+        for opening in &book.openings {
+            opening.validate().map_err(toml::de::Error::custom)?;
+        }
+        Ok(book)
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).unwrap()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let s = fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("failed to read {}: {}", path.display(), e));
+        Self::from_toml(&s).unwrap_or_else(|e| panic!("failed to parse {}: {}", path.display(), e))
+    }
+
+    /// Save in the same format as [OpeningBook::load].
+    pub fn save(&self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        fs::write(path, self.to_toml())
+            .unwrap_or_else(|e| panic!("failed to write {}: {}", path.display(), e));
+    }
+
+    /// Next placement of the first opening matching the board and the visible tumos.
+    pub fn pick(&self, player_state: &PlayerState) -> Option<Decision> {
+        let puyo_count: usize = player_state.board.height_array()[1..=WIDTH].iter().sum();
+        let visible: Vec<Tumo> = (0..player_state.tumos.available_tumo_len())
+            .map(|i| player_state.tumos[i])
+            .collect();
+        if puyo_count % 2 != 0 || visible.is_empty() {
+            return None;
+        }
+
+        self.openings
+            .iter()
+            .find_map(|opening| opening.pick(&player_state.board, puyo_count / 2, &visible))
+    }
+}
+
+impl Opening {
+    /// Opening placing `tumos` with `placements`. Colors are written as letters A to D.
+    pub fn new(name: String, tumos: &[Tumo], placements: &[Placement]) -> Self {
+        debug_assert_eq!(tumos.len(), placements.len());
+
+        let letter = |color: PuyoColor| {
+            let i = PuyoColor::normal_colors()
+                .iter()
+                .position(|&c| c == color)
+                .expect("tumos should be in normal colors");
+            LETTERS[i]
+        };
+
+        Self {
+            name,
+            tumos: tumos
+                .iter()
+                .map(|tumo| {
+                    [letter(tumo.axis()), letter(tumo.child())]
+                        .iter()
+                        .collect::<String>()
+                })
+                .join(" "),
+            placements: placements.iter().map(|p| (p.axis_x(), p.rot())).collect(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let pairs = self.tumos.split_whitespace().collect_vec();
+        if let Some(pair) = pairs
+            .iter()
+            .find(|pair| pair.len() != 2 || !pair.chars().all(|c| LETTERS.contains(&c)))
+        {
+            return Err(format!("{}: invalid pair `{}`", self.name, pair));
+        }
+        if pairs.len() != self.placements.len() {
+            return Err(format!(
+                "{}: {} pairs but {} placements",
+                self.name,
+                pairs.len(),
+                self.placements.len()
+            ));
+        }
+        if let Some(&(x, r)) = self
+            .placements
+            .iter()
+            .find(|&&(x, r)| !Placement::new(x, r).is_valid())
+        {
+            return Err(format!("{}: invalid placement [{}, {}]", self.name, x, r));
+        }
+        Ok(())
+    }
+
+    /// Tumos with letters A to D as red, green, blue and yellow.
+    fn pairs(&self) -> Vec<Tumo> {
+        let color = |c: char| {
+            let i = LETTERS.iter().position(|&l| l == c).unwrap();
+            PuyoColor::normal_colors()[i]
+        };
+
+        self.tumos
+            .split_whitespace()
+            .map(|pair| {
+                let (axis, child) = pair.chars().map(color).collect_tuple().unwrap();
+                Tumo::new(axis, child)
+            })
+            .collect()
+    }
+
+    fn pick(&self, board: &Board, turn: usize, visible: &[Tumo]) -> Option<Decision> {
+        let pairs = self.pairs();
+        let upcoming = pairs.get(turn..).filter(|p| !p.is_empty())?;
+        let len = upcoming.len().min(visible.len());
+        if tumo_pattern(&upcoming[..len]) != tumo_pattern(&visible[..len]) {
+            return None;
+        }
+
+        // find which real color each letter stands for
+        for colors in PuyoColor::normal_colors()
+            .iter()
+            .permutations(LETTERS.len())
+        {
+            let recolor = |tumo: &Tumo| {
+                let color = |c: PuyoColor| *colors[c as usize - PuyoColor::RED as usize];
+                Tumo::new(color(tumo.axis()), color(tumo.child()))
+            };
+            if !upcoming[..len]
+                .iter()
+                .zip(visible)
+                .all(|(pair, tumo)| is_same_pair(&recolor(pair), tumo))
+            {
+                continue;
+            }
+
+            let mut expected = Board::new();
+            for (pair, &(x, r)) in pairs[..turn].iter().zip(&self.placements) {
+                expected.place_tumo(&recolor(pair), &Placement::new(x, r))?;
+                expected.simulate();
+            }
+            if expected != *board {
+                continue;
+            }
+
+            let (x, r) = self.placements[turn];
+            let placement = Placement::new(x, r);
+            let placement = if recolor(&upcoming[0]).axis() == visible[0].axis() {
+                placement
+            } else {
+                placement.swapped()
+            };
+
+            return board.is_placeable(&placement).then(|| Decision {
+                placements: vec![placement],
+                chain: Chain::default(),
+                logging: Some(format!("opening: {}", self.name)),
+            });
+        }
+
+        None
+    }
+}
+
+fn is_same_pair(a: &Tumo, b: &Tumo) -> bool {
+    (a.axis(), a.child()) == (b.axis(), b.child()) || (a.axis(), a.child()) == (b.child(), b.axis())
+}
+
+/// Tumos normalized by color permutation (e.g. "AA AB").
+/// Letters are assigned in order of appearance. Pairs are unordered,
+/// so the smallest pattern among the orientations of the pairs is taken.
+pub fn tumo_pattern(tumos: &[Tumo]) -> String {
+    (0..1 << tumos.len())
+        .map(|flips: usize| {
+            let mut seen = Vec::with_capacity(LETTERS.len());
+            let mut letter = |color: PuyoColor| {
+                let i = seen.iter().position(|&c| c == color).unwrap_or_else(|| {
+                    seen.push(color);
+                    seen.len() - 1
+                });
+                LETTERS[i]
+            };
+
+            tumos
+                .iter()
+                .enumerate()
+                .map(|(i, tumo)| {
+                    let (a, b) = if flips >> i & 1 == 0 {
+                        (tumo.axis(), tumo.child())
+                    } else {
+                        (tumo.child(), tumo.axis())
+                    };
+                    let (a, b) = (letter(a), letter(b));
+                    [a.min(b), a.max(b)].iter().collect::<String>()
+                })
+                .join(" ")
+        })
+        .min()
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{color::PuyoColor::*, tumo::Tumos};
+
+    use super::*;
+
+    fn player_state(board: Board, tumos: &[Tumo]) -> PlayerState {
+        PlayerState::new(board, Tumos::new(tumos), 0, 0, 0, 0, 0, 0)
+    }
+
+    #[test]
+    fn pattern() {
+        assert_eq!(
+            tumo_pattern(&[Tumo::new(RED, RED), Tumo::new(GREEN, RED)]),
+            "AA AB"
+        );
+        assert_eq!(
+            tumo_pattern(&[Tumo::new(BLUE, YELLOW), Tumo::new(YELLOW, YELLOW)]),
+            "AB AA"
+        );
+        assert_eq!(
+            tumo_pattern(&[Tumo::new(BLUE, YELLOW), Tumo::new(RED, BLUE)]),
+            tumo_pattern(&[Tumo::new(YELLOW, BLUE), Tumo::new(BLUE, RED)]),
+        );
+    }
+
+    #[test]
+    fn default_book_builds_gtr() {
+        let book = OpeningBook::default();
+        assert!(!book.openings.is_empty());
+
+        for opening in &book.openings {
+            let mut board = Board::new();
+            for (pair, &(x, r)) in opening.pairs().iter().zip(&opening.placements) {
+                board.place_tumo(pair, &Placement::new(x, r)).unwrap();
+                assert_eq!(board.simulate().chain(), 0, "{}", opening.name);
+            }
+
+            // 1 more puyo on the 1st column fires 2 chain
+            let key = board.get(1, 3);
+            board
+                .place_tumo(&Tumo::new_zoro(key), &Placement::new(1, 0))
+                .unwrap();
+            assert_eq!(board.simulate().chain(), 2, "{}", opening.name);
+        }
+    }
+
+    #[test]
+    fn play_from_book() {
+        let book = OpeningBook::from_toml(concat!(
+            "[[openings]]\n",
+            "name = \"test\"\n",
+            "tumos = \"AA BB CA BA\"\n",
+            "placements = [[1, 1], [1, 1], [3, 0], [1, 1]]\n",
+        ))
+        .unwrap();
+
+        let pick = |board: Board, tumos: &[Tumo]| {
+            book.pick(&player_state(board, tumos))
+                .map(|d| d.placements[0])
+        };
+
+        // 1st tumo
+        assert_eq!(
+            pick(
+                Board::new(),
+                &[Tumo::new(YELLOW, YELLOW), Tumo::new(RED, RED)]
+            ),
+            Some(Placement::new(1, 1))
+        );
+        assert_eq!(
+            pick(
+                Board::new(),
+                &[Tumo::new(YELLOW, YELLOW), Tumo::new(RED, GREEN)]
+            ),
+            None
+        );
+
+        // 3rd tumo with the axis and child swapped
+        let board = Board::from(concat!(
+            "RR....", // 2
+            "YY....", // 1
+        ));
+        assert_eq!(
+            pick(
+                board.clone(),
+                &[Tumo::new(YELLOW, BLUE), Tumo::new(RED, YELLOW)]
+            ),
+            Some(Placement::new(3, 2))
+        );
+        // colors do not match the board
+        assert_eq!(
+            pick(board, &[Tumo::new(RED, BLUE), Tumo::new(YELLOW, BLUE)]),
+            None
+        );
+
+        // the board has left the book
+        let board = Board::from(concat!(
+            "RR....", // 2
+            ".YY...", // 1
+        ));
+        assert_eq!(
+            pick(board, &[Tumo::new(YELLOW, BLUE), Tumo::new(RED, YELLOW)]),
+            None
+        );
+    }
+
+    #[test]
+    fn new_opening() {
+        let opening = Opening::new(
+            "x".to_owned(),
+            &[Tumo::new(RED, RED), Tumo::new(YELLOW, GREEN)],
+            &[Placement::new(1, 1), Placement::new(3, 2)],
+        );
+
+        assert_eq!(opening.tumos, "AA DB");
+        assert_eq!(opening.placements, vec![(1, 1), (3, 2)]);
+        assert!(opening.validate().is_ok());
+    }
+
+    #[test]
+    fn invalid_book() {
+        let book = |tumos: &str, placements: &str| {
+            OpeningBook::from_toml(&format!(
+                "[[openings]]\nname = \"x\"\ntumos = \"{}\"\nplacements = {}\n",
+                tumos, placements
+            ))
+        };
+
+        assert!(book("AA AB", "[[1, 0], [2, 0]]").is_ok());
+        assert!(book("AA AE", "[[1, 0], [2, 0]]").is_err());
+        assert!(book("AA AB", "[[1, 0]]").is_err());
+        assert!(book("AA AB", "[[1, 0], [1, 3]]").is_err());
+        assert!(!OpeningBook::from_toml("").unwrap().openings.is_empty());
+    }
+}
@@ -0,0 +1,217 @@
+use core::{
+    board::{Board, HEIGHT, WIDTH},
+    chain::Chain,
+    ojama::{score_to_ojama, ZENKESHI_BONUS},
+    player_state::PlayerState,
+    search::ComplementedPuyo,
+};
+
+use crate::{chain_picker::enumerate_fireable_chains, decision::Decision};
+
+/// Rough frames to place a tumo, used to estimate when a potential chain is fired.
+pub(crate) const FRAMES_PER_TUMO: u32 = 40;
+
+/// The rest of the chain the opponent is firing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OngoingChain {
+    /// Chain from the current step to the end.
+    pub chain: Chain,
+    /// Ojama to be sent after offsetting.
+    pub ojama: u32,
+    /// Frames until the chain ends and the ojama is fixed.
+    pub frame: u32,
+}
+
+/// What the opponent can fire now and later.
+///
+/// While the opponent is firing a chain, the rest of it is forecasted,
+/// and the other chains are analysed on the board after it.
+#[derive(Clone, Default)]
+pub struct OpponentAnalysis {
+    /// Set if the opponent is firing a chain.
+    pub ongoing_chain: Option<OngoingChain>,
+    /// Chains fireable within the visible tumos after the ongoing chain. See [enumerate_fireable_chains].
+    pub fireable_chains: Vec<Decision>,
+    /// The biggest chain detected by [Board::detect_potential_chain].
+    pub potential_chain: Chain,
+    /// Number of puyos needed to fire [OpponentAnalysis::potential_chain].
+    pub potential_need: u32,
+    /// Ojama needed to fill the 3rd column.
+    pub ojama_to_kill: u32,
+    zenkeshi: bool,
+    frame: u32,
+    carry_over: u32,
+}
+
+impl OpponentAnalysis {
+    pub fn new(player_state: &PlayerState) -> Self {
+        let mut player_state = player_state.clone();
+        let ongoing_chain = (player_state.current_chain > 0).then(|| {
+            let chain = player_state
+                .board
+                .simulate_from_middle(player_state.current_chain);
+            let ojama = player_state.send_ojama(chain.score());
+            if chain.zenkeshi() {
+                player_state.zenkeshi = true;
+            }
+            player_state.current_chain = 0;
+
+            OngoingChain {
+                frame: chain.frame(),
+                ojama,
+                chain,
+            }
+        });
+
+        let mut potential_chain = Chain::default();
+        let mut potential_need = 0;
+        player_state.board.detect_potential_chain(
+            3,
+            1,
+            |_board: Board, fire_x: usize, cp: ComplementedPuyo, chain: Chain| {
+                if chain.score() > potential_chain.score() {
+                    potential_chain = chain;
+                    potential_need = cp.get(fire_x) as u32;
+                }
+            },
+        );
+
+        let height_3 = player_state.board.height_array()[3];
+
+        Self {
+            fireable_chains: enumerate_fireable_chains(&player_state),
+            potential_chain,
+            potential_need,
+            ojama_to_kill: (HEIGHT.saturating_sub(height_3) * WIDTH) as u32,
+            zenkeshi: player_state.zenkeshi,
+            frame: player_state.frame,
+            carry_over: player_state.carry_over,
+            ongoing_chain,
+        }
+    }
+
+    /// Ojama the ongoing chain sends, or 0 if not firing.
+    pub fn ongoing_ojama(&self) -> u32 {
+        self.ongoing_chain.as_ref().map_or(0, |c| c.ojama)
+    }
+
+    /// The biggest chain fireable with the first tumo.
+    pub fn fireable_now(&self) -> Option<&Decision> {
+        self.fireable_chains
+            .iter()
+            .filter(|d| d.placements.len() == 1)
+            .max_by_key(|d| d.chain.score())
+    }
+
+    /// Estimated frames to fire [OpponentAnalysis::potential_chain] and finish it.
+    pub fn potential_frame(&self) -> u32 {
+        self.potential_need.div_ceil(2) * FRAMES_PER_TUMO + self.potential_chain.frame()
+    }
+
+    /// Score of the biggest chain the opponent can finish within `frame`, excluding the ongoing one.
+    pub fn max_score_within(&self, frame: u32) -> u32 {
+        // the next chain can be fired only after the ongoing one ends
+        let busy = self.ongoing_chain.as_ref().map_or(0, |c| c.frame);
+        let Some(frame) = frame.checked_sub(busy) else {
+            return 0;
+        };
+
+        let fireable = self
+            .fireable_chains
+            .iter()
+            .filter(|d| d.chain.frame() <= frame)
+            .map(|d| d.chain.score())
+            .max()
+            .unwrap_or(0);
+
+        if self.potential_frame() <= frame {
+            fireable.max(self.potential_chain.score())
+        } else {
+            fireable
+        }
+    }
+
+    /// Ojama the opponent can send within `frame`, including the zenkeshi bonus.
+    pub fn max_ojama_within(&self, frame: u32) -> u32 {
+        let score = self.max_score_within(frame);
+        if score == 0 {
+            return 0;
+        }
+
+        let bonus = if self.zenkeshi { ZENKESHI_BONUS } else { 0 };
+        score_to_ojama(score + bonus, self.frame, self.carry_over).0
+    }
+
+    /// Score of the biggest chain the opponent can fire sooner or later.
+    pub fn max_score(&self) -> u32 {
+        self.max_score_within(u32::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{
+        color::PuyoColor::*,
+        tumo::{Tumo, Tumos},
+    };
+
+    use super::*;
+
+    #[test]
+    fn analyze() {
+        let board = Board::from(concat!(
+            "....B.", // 4
+            "....RB", // 3
+            "....RB", // 2
+            "....RB", // 1
+        ));
+        let tumos = Tumos::new(&[Tumo::new(RED, RED), Tumo::new(YELLOW, GREEN)]);
+        let player_state = PlayerState::new(board, tumos, 0, 0, 0, 0, 0, 0);
+
+        let analysis = OpponentAnalysis::new(&player_state);
+
+        assert_eq!(analysis.fireable_now().unwrap().chain.chain(), 2);
+        assert_eq!(analysis.potential_chain.chain(), 2);
+        assert_eq!(analysis.potential_need, 1);
+        assert_eq!(analysis.ojama_to_kill, 72);
+        assert_eq!(analysis.max_score_within(0), 0);
+        assert!(analysis.max_ojama_within(u32::MAX) >= 6);
+        assert_eq!(analysis.max_score(), analysis.max_score_within(u32::MAX));
+    }
+
+    #[test]
+    fn analyze_empty() {
+        let player_state = PlayerState::new(Board::new(), Tumos::default(), 0, 0, 0, 0, 0, 0);
+
+        let analysis = OpponentAnalysis::new(&player_state);
+
+        assert!(analysis.fireable_now().is_none());
+        assert!(analysis.ongoing_chain.is_none());
+        assert_eq!(analysis.max_score(), 0);
+        assert_eq!(analysis.max_ojama_within(u32::MAX), 0);
+    }
+
+    #[test]
+    fn forecast_ongoing_chain() {
+        // the 1st chain has been popped
+        let board = Board::from(concat!(
+            "....B.", // 2
+            "...BBB", // 1
+        ));
+        let mut player_state = PlayerState::new(board, Tumos::default(), 0, 0, 0, 2, 0, 2);
+
+        let analysis = OpponentAnalysis::new(&player_state);
+        let ongoing = analysis.ongoing_chain.as_ref().unwrap();
+
+        assert_eq!(ongoing.chain.chain(), 2);
+        // 4 * 10 * 8 = 320
+        assert_eq!(ongoing.chain.score(), 320);
+        assert_eq!(ongoing.ojama, 4 - 2);
+        assert!(ongoing.frame > 0);
+        assert_eq!(analysis.ongoing_ojama(), 2);
+        assert_eq!(analysis.max_score_within(ongoing.frame - 1), 0);
+
+        player_state.current_chain = 0;
+        assert!(OpponentAnalysis::new(&player_state).ongoing_chain.is_none());
+    }
+}
@@ -1,22 +1,28 @@
 mod beam_search;
+mod dfs;
 mod random;
-// mod dfs;
+mod search_limit;
+mod survival;
 
 use core::player_state::PlayerState;
 
 #[allow(unused_imports)]
 pub use {
-    beam_search::{BeamSearcher, ChokudaiSearcher, MonteCarloBeamSearcher},
+    beam_search::{BeamSearcher, ChokudaiSearcher, ExpectimaxSearcher, MonteCarloBeamSearcher},
+    dfs::DfsSearcher,
     random::RandomSearcher,
+    search_limit::SearchLimit,
+    survival::SurvivalSearcher,
 };
 
 use crate::{decision::Decision, evaluator::Evaluator};
 
 pub trait Searcher {
-    /// Returns (the best decision, list of chains that could be fired)
+    /// Returns the best decision found before `limit`.
     fn search(
+        &self,
         player_state: &PlayerState,
         evaluator: &Evaluator,
-        think_frame: Option<u32>,
+        limit: &SearchLimit,
     ) -> Decision;
 }
@@ -1,8 +1,11 @@
 mod beam_search;
 mod chokudai_search;
+mod expectimax_search;
 mod monte_carlo_beam_search;
-mod node;
+pub(super) mod node;
+pub(super) mod transposition_table;
 
 pub use beam_search::BeamSearcher;
 pub use chokudai_search::ChokudaiSearcher;
+pub use expectimax_search::ExpectimaxSearcher;
 pub use monte_carlo_beam_search::MonteCarloBeamSearcher;
@@ -1,107 +1,127 @@
-use core::{chain::Chain, placement::Placement, player_state::PlayerState};
+use core::{placement::Placement, player_state::PlayerState};
 
 use crate::{
     decision::Decision,
     evaluator::Evaluator,
     searcher::{
-        beam_search::node::{sort_by_eval, Node},
-        Searcher,
+        beam_search::{
+            node::{best_decision, sort_and_dedup_by_eval, Node},
+            transposition_table::TranspositionTable,
+        },
+        SearchLimit, Searcher,
     },
 };
 
+/// Beam search over the visible tumos, widening the beam until the limit.
 pub struct BeamSearcher;
 
+const INITIAL_WIDTH: usize = 22;
+/// Wider beam than this is almost exhaustive for 3 visible tumos.
+const MAX_WIDTH: usize = 22 * 22;
+
 impl Searcher for BeamSearcher {
     fn search(
+        &self,
         player_state: &PlayerState,
         evaluator: &Evaluator,
-        think_frame: Option<u32>,
+        limit: &SearchLimit,
     ) -> Decision {
-        let (depth, width) = get_best_depth_and_width(think_frame);
-        let depth = depth.min(player_state.tumos.available_tumo_len());
-
-        let mut nodes = vec![Node::from_player_state(player_state, &[], evaluator)];
-        let mut nxt_nodes = Vec::<Node>::with_capacity(width * 2);
-        let mut nxt_sorted;
-
-        for d in 0..depth {
-            let tumo = &player_state.tumos[d];
-            let placements_itr = if tumo.is_zoro() {
-                Placement::placements_zoro().iter()
-            } else {
-                Placement::placements_non_zoro().iter()
-            };
-
-            nxt_nodes.clear();
-            nxt_sorted = false;
-
-            for placement in placements_itr {
-                for node in &nodes {
-                    if !node.player_state.board.is_placeable(placement) {
-                        continue;
-                    }
-
-                    let nxt = node.place_tumo(tumo, placement, &evaluator);
-                    if nxt.player_state.board.is_dead() {
-                        continue;
-                    }
-
-                    if nxt_sorted && nxt_nodes[width - 1].eval_score > nxt.eval_score {
-                        continue;
-                    }
-                    nxt_nodes.push(nxt);
-
-                    if nxt_nodes.len() >= width * 2 {
-                        sort_by_eval(&mut nxt_nodes);
-                        nxt_nodes.resize(width, Node::default());
-                        nxt_sorted = true;
-                    }
-                }
-            }
-
-            if nxt_nodes.is_empty() {
-                break;
-            }
+        let mut table = TranspositionTable::new(evaluator);
 
-            sort_by_eval(&mut nxt_nodes);
-            if nxt_nodes.len() > width {
-                nxt_nodes.resize(width, Node::default());
-            }
-            nodes = nxt_nodes.clone();
+        if !limit.is_bounded() {
+            let depth = 2.min(player_state.tumos.available_tumo_len());
+            let nodes = beam_search(player_state, depth, INITIAL_WIDTH, &mut table, limit);
+            return best_decision(&nodes.unwrap_or_default(), evaluator);
         }
 
-        if nodes.is_empty() || nodes[0].placements.is_empty() {
-            return Decision {
-                placements: vec![Placement::new(3, 0)],
-                chain: Chain::default(),
-                logging: Some("muri...".to_owned()),
+        let depth = 3.min(player_state.tumos.available_tumo_len());
+        let mut best = beam_search(
+            player_state,
+            1,
+            INITIAL_WIDTH,
+            &mut table,
+            &SearchLimit::default(),
+        );
+        let mut width = INITIAL_WIDTH;
+        while width <= MAX_WIDTH {
+            let Some(nodes) = beam_search(player_state, depth, width, &mut table, limit) else {
+                break;
             };
+            best = Some(nodes);
+            width *= 2;
         }
 
-        Decision {
-            placements: nodes[0].placements.clone(),
-            chain: nodes[0].chain.clone(),
-            logging: Some(format!(
-                "eval: {:>6}\ntactics: {:>7}",
-                nodes[0].eval_score, evaluator.name
-            )),
-        }
+        best_decision(&best.unwrap_or_default(), evaluator)
     }
 }
 
-fn get_best_depth_and_width(think_frame: Option<u32>) -> (usize, usize) {
-    if let Some(frame) = think_frame {
-        if frame >= 24 {
-            (3, 121)
-        } else if frame >= 8 {
-            (3, 22)
+/// Run a beam search with `depth` and `width`, and return the nodes on the last depth sorted by eval.
+/// Return None if stopped by `limit` before completing it.
+pub(super) fn beam_search(
+    player_state: &PlayerState,
+    depth: usize,
+    width: usize,
+    table: &mut TranspositionTable,
+    limit: &SearchLimit,
+) -> Option<Vec<Node>> {
+    table.clear_visits();
+
+    let mut nodes = vec![Node::from_player_state(player_state, &[], table)];
+    let mut nxt_nodes = Vec::<Node>::with_capacity(width * 2);
+    let mut nxt_sorted;
+
+    for d in 0..depth {
+        let tumo = &player_state.tumos[d];
+        let placements_itr = if tumo.is_zoro() {
+            Placement::placements_zoro().iter()
         } else {
-            (2, 22)
+            Placement::placements_non_zoro().iter()
+        };
+
+        nxt_nodes.clear();
+        nxt_sorted = false;
+
+        for placement in placements_itr {
+            if limit.should_stop() {
+                return None;
+            }
+
+            for node in &nodes {
+                if !node.player_state.board.is_placeable(placement) {
+                    continue;
+                }
+
+                let nxt = node.place_tumo(tumo, placement, table);
+                if nxt.player_state.board.is_dead() {
+                    continue;
+                }
+                if !table.visit(nxt.hash, d, nxt.eval_score) {
+                    continue;
+                }
+
+                if nxt_sorted && nxt_nodes[width - 1].eval_score > nxt.eval_score {
+                    continue;
+                }
+                nxt_nodes.push(nxt);
+
+                if nxt_nodes.len() >= width * 2 {
+                    sort_and_dedup_by_eval(&mut nxt_nodes);
+                    nxt_nodes.truncate(width);
+                    nxt_sorted = nxt_nodes.len() >= width;
+                }
+            }
         }
-    } else {
-        // TODO: impl simple DFS instead
-        (2, 22)
+
+        if nxt_nodes.is_empty() {
+            break;
+        }
+
+        sort_and_dedup_by_eval(&mut nxt_nodes);
+        nxt_nodes.truncate(width);
+        nodes = nxt_nodes.clone();
     }
+
+    Some(nodes)
 }
 
 #[cfg(test)]
@@ -135,11 +155,39 @@ mod tests {
         for board in &boards {
             for tumos in &tumos_pattern {
                 let player_state = PlayerState::new(board.clone(), tumos.clone(), 0, 0, 0, 0, 0, 0);
-                let decision = BeamSearcher::search(&player_state, &BUILD, None);
+                let decision = BeamSearcher.search(&player_state, &BUILD, &SearchLimit::default());
 
                 assert!(!decision.placements.is_empty());
                 assert!(board.is_placeable(decision.placements.first().unwrap()));
             }
         }
     }
+
+    #[test]
+    fn fire_to_avoid_fixed_ojama() {
+        let board = Board::from(concat!(
+            "..Y...", // 11
+            "..G...", // 10
+            "..B...", // 9
+            "..Y...", // 8
+            "..G...", // 7
+            "..B...", // 6
+            "..Y...", // 5
+            "..G.B.", // 4
+            "..B.RB", // 3
+            "..Y.RB", // 2
+            "..G.RB", // 1
+        ));
+        let tumos = Tumos::new(&[Tumo::new(RED, RED)]);
+        let search = |ojama_fixed: u32| {
+            let player_state =
+                PlayerState::new(board.clone(), tumos.clone(), 0, 0, 0, ojama_fixed, 0, 0);
+            BeamSearcher.search(&player_state, &BUILD, &SearchLimit::default())
+        };
+
+        // a small chain is not worth firing
+        assert_eq!(search(0).chain.chain(), 0);
+        // a row of ojama fills the 3rd column unless the 2 chain offsets all of it
+        assert_eq!(search(6).chain.chain(), 2);
+    }
 }
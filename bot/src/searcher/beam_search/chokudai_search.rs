@@ -1,125 +1,144 @@
-use core::{chain::Chain, placement::Placement, player_state::PlayerState};
-use std::time::Instant;
+use core::{placement::Placement, player_state::PlayerState};
+use std::{cmp::Ordering, collections::BinaryHeap};
 
 use crate::{
     decision::Decision,
     evaluator::Evaluator,
     searcher::{
-        beam_search::node::{sort_by_eval, Node},
-        Searcher,
+        beam_search::{
+            node::{best_decision, Node},
+            transposition_table::TranspositionTable,
+        },
+        SearchLimit, Searcher,
     },
 };
 
-/// First perform BFS up to depth=2, then continue the search until 84% of the think_frame time has passed.
+/// Keep one priority queue for each depth, and expand the best node of every depth in turn
+/// until the limit. Tumos beyond the visible ones are filled randomly.
+/// Each queue is bounded by [ChokudaiSearcher::WIDTH] like a beam.
+///
+/// With an unbounded limit, [ChokudaiSearcher::PASSES] passes are done.
 pub struct ChokudaiSearcher;
 
-const MIN_DEPTH: usize = 2;
+impl ChokudaiSearcher {
+    /// Depth of the search including the random tumos.
+    pub const DEPTH: usize = 14;
+    /// Number of passes for the fixed-size search.
+    pub const PASSES: usize = 8;
+    /// Number of the best nodes kept in the queue of each depth.
+    pub const WIDTH: usize = 44;
+}
 
 impl Searcher for ChokudaiSearcher {
     fn search(
+        &self,
         player_state: &PlayerState,
         evaluator: &Evaluator,
-        think_frame: Option<u32>,
+        limit: &SearchLimit,
     ) -> Decision {
-        let start = Instant::now();
-
-        let mut nodes = vec![Node::from_player_state(player_state, &[], evaluator)];
-        let mut nxt_nodes = Vec::<Node>::with_capacity(22 * 22);
+        let mut player_state = player_state.clone();
+        // monte carlo
+        let visible = player_state.tumos.available_tumo_len();
+        if Self::DEPTH > visible {
+            player_state.tumos.extend_randoms(Self::DEPTH - visible);
+        }
 
-        for d in 0..MIN_DEPTH {
-            let tumo = &player_state.tumos[d];
-            let placements_itr = if tumo.is_zoro() {
-                Placement::placements_zoro().iter()
+        let mut table = TranspositionTable::new(evaluator);
+        let mut queues: Vec<BinaryHeap<ByEval>> = vec![BinaryHeap::new(); Self::DEPTH + 1];
+        queues[0].push(ByEval(Node::from_player_state(
+            &player_state,
+            &[],
+            &mut table,
+        )));
+
+        let mut pass = 0;
+        // the first pass is done regardless of the limit, so that there's always some decision
+        let in_time = |pass: usize| {
+            if limit.is_bounded() {
+                !limit.should_stop()
             } else {
-                Placement::placements_non_zoro().iter()
-            };
-
-            nxt_nodes.clear();
+                pass < Self::PASSES
+            }
+        };
+        while pass == 0 || in_time(pass) {
+            let mut expanded = false;
 
-            for placement in placements_itr {
-                for node in &nodes {
+            for d in 0..Self::DEPTH {
+                if pass > 0 && limit.should_stop() {
+                    break;
+                }
+                let Some(ByEval(node)) = queues[d].pop() else {
+                    continue;
+                };
+                expanded = true;
+
+                let tumo = &player_state.tumos[d];
+                let placements_itr = if tumo.is_zoro() {
+                    Placement::placements_zoro().iter()
+                } else {
+                    Placement::placements_non_zoro().iter()
+                };
+
+                for placement in placements_itr {
                     if !node.player_state.board.is_placeable(placement) {
                         continue;
                     }
 
-                    let nxt = node.place_tumo(tumo, placement, &evaluator);
-                    if node.player_state.board.is_dead() {
+                    let nxt = node.place_tumo(tumo, placement, &mut table);
+                    if nxt.player_state.board.is_dead() {
+                        continue;
+                    }
+                    if !table.visit(nxt.hash, d, nxt.eval_score) {
                         continue;
                     }
 
-                    nxt_nodes.push(nxt);
+                    queues[d + 1].push(ByEval(nxt));
+                }
+                if queues[d + 1].len() > Self::WIDTH * 2 {
+                    truncate(&mut queues[d + 1], Self::WIDTH);
                 }
             }
 
-            if nxt_nodes.is_empty() {
+            if !expanded {
                 break;
             }
-
-            sort_by_eval(&mut nxt_nodes);
-            nodes = nxt_nodes.clone();
+            pass += 1;
         }
 
-        if nodes.is_empty() || nodes[0].placements.is_empty() {
-            return Decision {
-                placements: vec![Placement::new(3, 0)],
-                chain: Chain::default(),
-                logging: Some("muri...".to_owned()),
-            };
-        }
+        // best node on the deepest depth reached
+        let best = queues.into_iter().rev().find_map(|mut queue| queue.pop());
+        best_decision(best.map(|ByEval(node)| node).as_slice(), evaluator)
+    }
+}
 
-        let mut best_node: Option<Node> = None;
+/// Keep only the best `width` nodes of `queue`.
+fn truncate(queue: &mut BinaryHeap<ByEval>, width: usize) {
+    let mut nodes = std::mem::take(queue).into_sorted_vec();
+    nodes.drain(..nodes.len().saturating_sub(width));
+    *queue = nodes.into();
+}
 
-        if player_state.tumos.len() >= MIN_DEPTH + 1 {
-            let tumo = &player_state.tumos[MIN_DEPTH];
-            let placements_itr = if tumo.is_zoro() {
-                Placement::placements_zoro().iter()
-            } else {
-                Placement::placements_non_zoro().iter()
-            };
-
-            if let Some(frame) = think_frame {
-                let think_frame_ms_84_perc = frame as u128 * (840 / 60);
-                for placement in placements_itr.as_ref() {
-                    for node in &nodes {
-                        if start.elapsed().as_millis() > think_frame_ms_84_perc {
-                            break;
-                        }
-
-                        if !node.player_state.board.is_placeable(placement) {
-                            continue;
-                        }
-
-                        let nxt = node.place_tumo(tumo, placement, &evaluator);
-                        if nxt.player_state.board.is_dead() {
-                            continue;
-                        }
-
-                        if let Some(ref best) = best_node {
-                            if best.eval_score < nxt.eval_score {
-                                best_node = Some(nxt);
-                            }
-                        } else {
-                            best_node = Some(nxt);
-                        }
-                    }
-                }
-            }
-        }
+/// [Node] ordered by eval score.
+#[derive(Clone)]
+struct ByEval(Node);
 
-        let best_node = if let Some(best) = best_node {
-            best
-        } else {
-            nodes[0].clone()
-        };
+impl PartialEq for ByEval {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.eval_score == other.0.eval_score
+    }
+}
 
-        Decision {
-            placements: best_node.placements.clone(),
-            chain: best_node.chain.clone(),
-            logging: Some(format!(
-                "eval: {:>6}\ntactics: {:>7}",
-                best_node.eval_score, evaluator.name
-            )),
-        }
+impl Eq for ByEval {}
+
+impl PartialOrd for ByEval {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ByEval {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.eval_score.cmp(&other.0.eval_score)
     }
 }
 
@@ -130,6 +149,7 @@ mod tests {
         color::PuyoColor::*,
         tumo::{Tumo, Tumos},
     };
+    use std::time::{Duration, Instant};
 
     use super::*;
     use crate::evaluator::BUILD;
@@ -154,11 +174,57 @@ mod tests {
         for board in &boards {
             for tumos in &tumos_pattern {
                 let player_state = PlayerState::new(board.clone(), tumos.clone(), 0, 0, 0, 0, 0, 0);
-                let decision = ChokudaiSearcher::search(&player_state, &BUILD, None);
+                let decision =
+                    ChokudaiSearcher.search(&player_state, &BUILD, &SearchLimit::default());
 
                 assert!(!decision.placements.is_empty());
                 assert!(board.is_placeable(decision.placements.first().unwrap()));
             }
         }
     }
+
+    #[test]
+    fn truncate_keeps_best() {
+        let mut queue: BinaryHeap<ByEval> = [3, 1, 4, 1, 5, 9, 2, 6]
+            .into_iter()
+            .map(|eval_score| {
+                ByEval(Node {
+                    eval_score,
+                    ..Node::default()
+                })
+            })
+            .collect();
+
+        truncate(&mut queue, 3);
+        let evals: Vec<i32> = queue
+            .into_sorted_vec()
+            .iter()
+            .map(|n| n.0.eval_score)
+            .collect();
+        assert_eq!(evals, [5, 6, 9]);
+    }
+
+    #[test]
+    fn search_stops_at_limit() {
+        let board = Board::from(concat!(
+            "G.....", // 4
+            "GG..Y.", // 3
+            "RBBGY.", // 2
+            "RRBGG.", // 1
+        ));
+        let tumos = Tumos::new(&[
+            Tumo::new(RED, GREEN),
+            Tumo::new(BLUE, YELLOW),
+            Tumo::new(YELLOW, GREEN),
+        ]);
+        let player_state = PlayerState::new(board.clone(), tumos, 0, 0, 0, 0, 0, 0);
+
+        let start = Instant::now();
+        let limit = SearchLimit::from_millis(start, 100);
+        let decision = ChokudaiSearcher.search(&player_state, &BUILD, &limit);
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(board.is_placeable(decision.placements.first().unwrap()));
+        // the line goes beyond the visible tumos
+        assert_eq!(decision.placements.len(), ChokudaiSearcher::DEPTH);
+    }
 }
@@ -0,0 +1,241 @@
+use core::{placement::Placement, player_state::PlayerState, tumo::Tumo};
+
+use crate::{
+    decision::Decision,
+    evaluator::Evaluator,
+    searcher::{
+        beam_search::{
+            beam_search::beam_search,
+            node::{best_decision, Node},
+            transposition_table::TranspositionTable,
+        },
+        SearchLimit, Searcher,
+    },
+};
+
+/// Beam search over the visible tumos, then pick the candidate with the best expected eval
+/// over the next tumo. If the next tumo is unseen, it is a chance node of the distinct pairs
+/// weighted by their probabilities given the colors dealt so far in the loop.
+///
+/// With a bounded limit, candidates are evaluated from the best one until the limit.
+/// Otherwise only [ExpectimaxSearcher::CANDIDATES] candidates are evaluated.
+pub struct ExpectimaxSearcher;
+
+impl ExpectimaxSearcher {
+    /// Number of candidates for the fixed-size search.
+    pub const CANDIDATES: usize = 8;
+}
+
+const MAX_DEPTH: usize = 3;
+const WIDTH: usize = 44;
+/// Eval of an outcome where every placement dies. It is finite so that the expectation is
+/// still an average, but low enough to outweigh any other eval.
+const DEATH_EVAL: i32 = -1_000_000;
+
+impl Searcher for ExpectimaxSearcher {
+    fn search(
+        &self,
+        player_state: &PlayerState,
+        evaluator: &Evaluator,
+        limit: &SearchLimit,
+    ) -> Decision {
+        let mut table = TranspositionTable::new(evaluator);
+
+        let available = player_state.tumos.available_tumo_len();
+        let depth = MAX_DEPTH.min(available);
+        let Some(nodes) = beam_search(player_state, depth, WIDTH, &mut table, limit) else {
+            // out of time, so take the best placement by eval
+            let nodes = beam_search(player_state, 1, WIDTH, &mut table, &SearchLimit::default());
+            return best_decision(&nodes.unwrap_or_default(), evaluator);
+        };
+
+        if nodes.is_empty() || nodes[0].placements.is_empty() {
+            return best_decision(&nodes, evaluator);
+        }
+
+        let outcomes = if depth < available {
+            vec![(player_state.tumos[depth], 1)]
+        } else {
+            player_state.tumos.distribution().next_pairs()
+        };
+        let candidates = if limit.is_bounded() {
+            nodes.len()
+        } else {
+            Self::CANDIDATES
+        };
+
+        let mut best: Option<(i64, &Node)> = None;
+        for (i, node) in nodes.iter().take(candidates).enumerate() {
+            // the best candidate by eval is always taken into account
+            if i > 0 && limit.should_stop() {
+                break;
+            }
+
+            let expected = expected_eval(node, &outcomes, &mut table);
+            if best.map_or(true, |(best_expected, _)| best_expected < expected) {
+                best = Some((expected, node));
+            }
+        }
+
+        let (expected, best) = best.unwrap();
+        Decision {
+            placements: best.placements.clone(),
+            chain: best.chain.clone(),
+            logging: Some(format!(
+                "expected: {:>6}\ntactics: {:>7}",
+                expected, evaluator.name
+            )),
+        }
+    }
+}
+
+/// Average of the best eval after placing the next tumo, which is one of `outcomes`
+/// weighted by the count.
+fn expected_eval(node: &Node, outcomes: &[(Tumo, u32)], table: &mut TranspositionTable) -> i64 {
+    let mut sum = 0;
+    let mut total = 0;
+    for (tumo, count) in outcomes {
+        sum += *count as i64 * best_eval(node, tumo, table) as i64;
+        total += *count as i64;
+    }
+    sum / total
+}
+
+/// Best eval after placing `tumo` on `node`, or [DEATH_EVAL] if every placement dies.
+fn best_eval(node: &Node, tumo: &Tumo, table: &mut TranspositionTable) -> i32 {
+    let placements_itr = if tumo.is_zoro() {
+        Placement::placements_zoro().iter()
+    } else {
+        Placement::placements_non_zoro().iter()
+    };
+
+    let mut best = DEATH_EVAL;
+    for placement in placements_itr {
+        if !node.player_state.board.is_placeable(placement) {
+            continue;
+        }
+
+        let nxt = node.place_tumo(tumo, placement, table);
+        if !nxt.player_state.board.is_dead() {
+            best = best.max(nxt.eval_score);
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{board::Board, color::PuyoColor::*, tumo::Tumos};
+    use std::time::Instant;
+
+    use super::*;
+    use crate::evaluator::BUILD;
+
+    #[test]
+    fn search_returns_valid_placement() {
+        let boards: [Board; 8] = [
+            [0, 0, 0, 0, 0, 0].into(),
+            [11, 11, 11, 11, 11, 11].into(),
+            [12, 12, 11, 12, 12, 12].into(),
+            [11, 11, 11, 13, 11, 11].into(),
+            [11, 13, 11, 11, 11, 11].into(),
+            [11, 12, 11, 13, 11, 11].into(),
+            [11, 13, 11, 12, 11, 11].into(),
+            [11, 13, 11, 13, 11, 11].into(),
+        ];
+        let tumos_pattern: [Tumos; 2] = [
+            Tumos::new(&[Tumo::new(RED, GREEN), Tumo::new(BLUE, GREEN)]),
+            Tumos::new(&[Tumo::new_zoro(BLUE)]),
+        ];
+
+        for board in &boards {
+            for tumos in &tumos_pattern {
+                let player_state = PlayerState::new(board.clone(), tumos.clone(), 0, 0, 0, 0, 0, 0);
+                let decision =
+                    ExpectimaxSearcher.search(&player_state, &BUILD, &SearchLimit::default());
+
+                assert!(!decision.placements.is_empty());
+                assert!(board.is_placeable(decision.placements.first().unwrap()));
+            }
+        }
+    }
+
+    #[test]
+    fn search_until_deadline() {
+        let player_state = PlayerState::new(
+            Board::new(),
+            Tumos::new(&[Tumo::new(RED, GREEN), Tumo::new(BLUE, GREEN)]),
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+        );
+        let limit = SearchLimit::with_deadline(Instant::now());
+        let decision = ExpectimaxSearcher.search(&player_state, &BUILD, &limit);
+
+        assert_eq!(decision.placements.len(), 1);
+    }
+
+    #[test]
+    fn expectation_over_outcomes() {
+        let board = Board::from(concat!(
+            "RY....", // 3
+            "RBBG..", // 2
+            "YBGG..", // 1
+        ));
+        let player_state =
+            PlayerState::new(board, Tumos::new(&[Tumo::new(RED, RED)]), 0, 0, 0, 0, 0, 0);
+        let mut table = TranspositionTable::new(&BUILD);
+        let node = Node::from_player_state(&player_state, &[], &mut table);
+
+        let tumo = Tumo::new(RED, BLUE);
+        assert_eq!(
+            expected_eval(&node, &[(tumo, 1)], &mut table),
+            best_eval(&node, &tumo, &mut table) as i64
+        );
+
+        // the expectation is between the worst and the best outcome
+        let outcomes = player_state.tumos.distribution().next_pairs();
+        let evals: Vec<i64> = outcomes
+            .iter()
+            .map(|(tumo, _)| best_eval(&node, tumo, &mut table) as i64)
+            .collect();
+        let expected = expected_eval(&node, &outcomes, &mut table);
+        assert!(*evals.iter().min().unwrap() <= expected);
+        assert!(expected <= *evals.iter().max().unwrap());
+    }
+
+    #[test]
+    fn expectation_with_death() {
+        let board = Board::from(concat!(
+            "..Y...", // 11
+            "..G...", // 10
+            "..B...", // 9
+            "..Y...", // 8
+            "..G...", // 7
+            "..B...", // 6
+            "..Y...", // 5
+            "..G.B.", // 4
+            "..B.RB", // 3
+            "..Y.RB", // 2
+            "..G.RB", // 1
+        ));
+        // a row of ojama fills the 3rd column unless the 2 chain offsets all of it,
+        // which a 1 chain of blue does not
+        let player_state = PlayerState::new(board, Tumos::default(), 0, 0, 0, 6, 0, 0);
+        let mut table = TranspositionTable::new(&BUILD);
+        let node = Node::from_player_state(&player_state, &[], &mut table);
+
+        let (dead, alive) = (Tumo::new_zoro(BLUE), Tumo::new_zoro(RED));
+        assert_eq!(best_eval(&node, &dead, &mut table), DEATH_EVAL);
+        let alive_eval = best_eval(&node, &alive, &mut table);
+        assert!(alive_eval > DEATH_EVAL);
+
+        assert_eq!(
+            expected_eval(&node, &[(dead, 1), (alive, 3)], &mut table),
+            (DEATH_EVAL as i64 + 3 * alive_eval as i64) / 4
+        );
+    }
+}
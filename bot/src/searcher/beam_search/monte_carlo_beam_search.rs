@@ -1,46 +1,86 @@
-use core::{chain::Chain, placement::Placement, player_state::PlayerState};
-use std::{sync::mpsc, thread};
+use core::{placement::Placement, player_state::PlayerState};
+use std::sync::Arc;
 
 use crate::{
     decision::Decision,
     evaluator::Evaluator,
     searcher::{
-        beam_search::node::{sort_by_eval, Node},
-        Searcher,
+        beam_search::{
+            beam_search::beam_search, node::best_decision, transposition_table::TranspositionTable,
+        },
+        SearchLimit, Searcher,
     },
+    WorkerPool,
 };
 
-/// Number of threads available.
-const PARALLEL: usize = 20;
+/// (depth, width) of each iteration. Deepen and widen the search until the limit.
+const SCHEDULE: &[(usize, usize)] = &[(3, 22), (6, 22), (14, 44), (30, 100)];
 
-pub struct MonteCarloBeamSearcher;
+/// Beam search on random tumo sequences sampled in parallel, then take the majority vote.
+///
+/// With a bounded limit, one sample per thread is searched until the limit.
+/// Otherwise `samples` samples are searched with the fixed size.
+pub struct MonteCarloBeamSearcher {
+    pool: Arc<WorkerPool>,
+    samples: usize,
+}
+
+impl MonteCarloBeamSearcher {
+    /// Number of samples (= tumo sequences) for the fixed-size search.
+    pub const SAMPLES: usize = 20;
+
+    pub fn new(pool: Arc<WorkerPool>) -> Self {
+        Self {
+            pool,
+            samples: Self::SAMPLES,
+        }
+    }
+
+    pub fn with_samples(self, samples: usize) -> Self {
+        Self { samples, ..self }
+    }
+}
 
 impl Searcher for MonteCarloBeamSearcher {
     fn search(
+        &self,
         player_state: &PlayerState,
         evaluator: &Evaluator,
-        think_frame: Option<u32>,
+        limit: &SearchLimit,
     ) -> Decision {
-        let (depth, width) = get_best_depth_and_width(think_frame);
-        let parallel_n = if player_state.tumos.available_tumo_len() < depth {
-            PARALLEL
+        // For SPSA
+        let schedule = if limit.is_bounded() {
+            SCHEDULE
         } else {
+            &SCHEDULE[..1]
+        };
+
+        let max_depth = schedule.last().unwrap().0;
+        let parallel_n = if player_state.tumos.available_tumo_len() >= max_depth {
             1
+        } else if limit.is_bounded() {
+            self.pool.threads()
+        } else {
+            self.samples
         };
 
-        let (decision_sender, decision_receiver) = mpsc::channel();
-        for _ in 0..parallel_n {
-            let decision_sender = decision_sender.clone();
-            let player_state = player_state.clone();
-            let evaluator = evaluator.clone();
-
-            thread::spawn(move || {
-                decision_sender
-                    .send(search_single_thread(depth, width, player_state, evaluator))
-                    .ok();
-            });
+        let jobs = (0..parallel_n)
+            .map(|_| {
+                let player_state = player_state.clone();
+                let evaluator = *evaluator;
+                let limit = limit.clone();
+
+                move || search_single_thread(schedule, player_state, evaluator, limit)
+            })
+            .collect();
+        let decisions: Vec<Decision> = self.pool.run(jobs).into_iter().flatten().collect();
+
+        // no iteration completed in time, so take the best placement by eval
+        if decisions.is_empty() {
+            let mut table = TranspositionTable::new(evaluator);
+            let nodes = beam_search(player_state, 1, 22, &mut table, &SearchLimit::default());
+            return best_decision(&nodes.unwrap_or_default(), evaluator);
         }
-        let decisions: Vec<Decision> = decision_receiver.iter().take(parallel_n).collect();
 
         let mut scores = [[0; 4]; 7];
         for decision in &decisions {
@@ -59,99 +99,30 @@ impl Searcher for MonteCarloBeamSearcher {
     }
 }
 
-fn get_best_depth_and_width(think_frame: Option<u32>) -> (usize, usize) {
-    if let Some(frame) = think_frame {
-        if frame >= 24 {
-            (30, 100)
-        } else if frame >= 8 {
-            (14, 44)
-        } else {
-            (6, 22)
-        }
-    } else {
-        // For SPSA
-        (3, 22)
-    }
-}
-
+/// Return the decision of the last iteration completed before the limit.
 fn search_single_thread(
-    depth: usize,
-    width: usize,
+    schedule: &[(usize, usize)],
     mut player_state: PlayerState,
     evaluator: Evaluator,
-) -> Decision {
-    // monte carlo
-    let visible = player_state.tumos.available_tumo_len();
-    if depth > visible {
-        player_state.tumos.extend_randoms(depth - visible);
-    }
-
-    let mut nodes = vec![Node::from_player_state(&player_state, &[], &evaluator)];
-    let mut nxt_nodes = Vec::<Node>::with_capacity(width * 2);
-    let mut nxt_sorted;
-
-    for d in 0..depth {
-        let tumo = &player_state.tumos[d];
-        let placements_itr = if tumo.is_zoro() {
-            Placement::placements_zoro().iter()
-        } else {
-            Placement::placements_non_zoro().iter()
-        };
-
-        nxt_nodes.clear();
-        nxt_sorted = false;
-
-        for placement in placements_itr {
-            for node in &nodes {
-                if !node.player_state.board.is_placeable(placement) {
-                    continue;
-                }
-
-                let nxt = node.place_tumo(tumo, placement, &evaluator);
-                if nxt.player_state.board.is_dead() {
-                    continue;
-                }
-
-                if nxt_sorted && nxt_nodes[width - 1].eval_score > nxt.eval_score {
-                    continue;
-                }
-                nxt_nodes.push(nxt);
-
-                if nxt_nodes.len() >= width * 2 {
-                    sort_by_eval(&mut nxt_nodes);
-                    nxt_nodes.resize(width, Node::default());
-                    nxt_sorted = true;
-                }
-            }
+    limit: SearchLimit,
+) -> Option<Decision> {
+    let mut table = TranspositionTable::new(&evaluator);
+    let mut decision = None;
+
+    for &(depth, width) in schedule {
+        // monte carlo
+        let visible = player_state.tumos.available_tumo_len();
+        if depth > visible {
+            player_state.tumos.extend_randoms(depth - visible);
         }
 
-        if nxt_nodes.is_empty() {
+        let Some(nodes) = beam_search(&player_state, depth, width, &mut table, &limit) else {
             break;
-        }
-
-        sort_by_eval(&mut nxt_nodes);
-        if nxt_nodes.len() > width {
-            nxt_nodes.resize(width, Node::default());
-        }
-        nodes = nxt_nodes.clone();
-    }
-
-    if nodes.is_empty() || nodes[0].placements.is_empty() {
-        return Decision {
-            placements: vec![Placement::new(3, 0)],
-            chain: Chain::default(),
-            logging: Some("muri...".to_owned()),
         };
+        decision = Some(best_decision(&nodes, &evaluator));
     }
 
-    Decision {
-        placements: nodes[0].placements.clone(),
-        chain: nodes[0].chain.clone(),
-        logging: Some(format!(
-            "eval: {:>6}\ntactics: {:>7}",
-            nodes[0].eval_score, evaluator.name
-        )),
-    }
+    decision
 }
 
 #[cfg(test)]
@@ -161,10 +132,18 @@ mod tests {
         color::PuyoColor::*,
         tumo::{Tumo, Tumos},
     };
+    use std::{
+        sync::atomic::AtomicBool,
+        time::{Duration, Instant},
+    };
 
     use super::*;
     use crate::evaluator::BUILD;
 
+    fn searcher() -> MonteCarloBeamSearcher {
+        MonteCarloBeamSearcher::new(Arc::new(WorkerPool::new(2)))
+    }
+
     #[test]
     fn search_returns_valid_placement() {
         let boards: [Board; 8] = [
@@ -185,11 +164,38 @@ mod tests {
         for board in &boards {
             for tumos in &tumos_pattern {
                 let player_state = PlayerState::new(board.clone(), tumos.clone(), 0, 0, 0, 0, 0, 0);
-                let decision = MonteCarloBeamSearcher::search(&player_state, &BUILD, None);
+                let decision = searcher().search(&player_state, &BUILD, &SearchLimit::default());
 
                 assert!(!decision.placements.is_empty());
                 assert!(board.is_placeable(decision.placements.first().unwrap()));
             }
         }
     }
+
+    #[test]
+    fn search_stops_at_limit() {
+        let board = Board::from(concat!(
+            "G.....", // 4
+            "GG..Y.", // 3
+            "RBBGY.", // 2
+            "RRBGG.", // 1
+        ));
+        let tumos = Tumos::new(&[
+            Tumo::new(RED, GREEN),
+            Tumo::new(BLUE, YELLOW),
+            Tumo::new(YELLOW, GREEN),
+        ]);
+        let player_state = PlayerState::new(board.clone(), tumos, 0, 0, 0, 0, 0, 0);
+
+        let start = Instant::now();
+        let limit = SearchLimit::from_millis(start, 100);
+        let decision = searcher().search(&player_state, &BUILD, &limit);
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(board.is_placeable(decision.placements.first().unwrap()));
+
+        let stop = Arc::new(AtomicBool::new(true));
+        let limit = SearchLimit::default().with_stop(stop);
+        let decision = searcher().search(&player_state, &BUILD, &limit);
+        assert!(board.is_placeable(decision.placements.first().unwrap()));
+    }
 }
@@ -1,44 +1,65 @@
 use core::{chain::Chain, placement::Placement, player_state::PlayerState, tumo::Tumo};
+use std::{
+    collections::{hash_map::DefaultHasher, HashSet},
+    hash::{Hash, Hasher},
+};
 
-use crate::{evaluator::Evaluator, DetailedPlayerState};
+use super::transposition_table::TranspositionTable;
+use crate::{decision::Decision, evaluator::Evaluator, DetailedPlayerState};
 
 #[derive(Clone, Default)]
-pub(super) struct Node {
+pub(in crate::searcher) struct Node {
     pub eval_score: i32,
     pub chain: Chain,
     pub player_state: DetailedPlayerState,
     pub placements: Vec<Placement>,
+    /// Hash of the board and the rest of the state the search depends on. See [state_hash].
+    pub hash: u64,
 }
 
 impl Node {
     pub fn from_player_state(
         player_state: &PlayerState,
         placements: &[Placement],
-        evaluator: &Evaluator,
+        table: &mut TranspositionTable,
     ) -> Self {
-        Self::from_detailed_player_state(&player_state.clone().into(), placements, evaluator)
+        Self::from_detailed_player_state(&player_state.clone().into(), placements, table)
     }
 
     fn from_detailed_player_state(
         player_state: &DetailedPlayerState,
         placements: &[Placement],
-        evaluator: &Evaluator,
+        table: &mut TranspositionTable,
     ) -> Self {
         let mut new_player_state = player_state.clone();
-        let chain = new_player_state.board.simulate();
-        new_player_state.frame += chain.frame();
-        new_player_state.frame_since_control_start += chain.frame();
-        new_player_state.frame_by_chain += chain.frame();
+        let chain = simulate(&mut new_player_state);
+
+        Self::with_chain(new_player_state, chain, placements, table)
+    }
+
+    fn with_chain(
+        player_state: DetailedPlayerState,
+        chain: Chain,
+        placements: &[Placement],
+        table: &mut TranspositionTable,
+    ) -> Self {
+        let board_hash = player_state.board.zobrist_hash();
 
         Self {
-            eval_score: evaluator.evaluate(&new_player_state),
-            chain: chain,
-            player_state: new_player_state,
+            eval_score: table.evaluate(&player_state, board_hash),
+            hash: state_hash(&player_state, board_hash),
+            chain,
+            player_state,
             placements: placements.into(),
         }
     }
 
-    pub fn place_tumo(&self, tumo: &Tumo, placement: &Placement, evaluator: &Evaluator) -> Self {
+    pub fn place_tumo(
+        &self,
+        tumo: &Tumo,
+        placement: &Placement,
+        table: &mut TranspositionTable,
+    ) -> Self {
         let mut new_player_state = self.player_state.clone();
         new_player_state.frame_by_chigiri += new_player_state.board.chigiri_frames(placement);
         let (place_frame, bonus) = new_player_state.board.place_tumo(tumo, placement).unwrap();
@@ -49,10 +70,157 @@ impl Node {
         let mut new_placements = self.placements.clone();
         new_placements.push(*placement);
 
-        Self::from_detailed_player_state(&new_player_state, &new_placements, evaluator)
+        // fixed ojama left after offsetting drops once the chain ends
+        let chain = simulate(&mut new_player_state);
+        let ojama_frame = new_player_state.drop_fixed_ojama_conservatively();
+        new_player_state.frame += ojama_frame;
+        new_player_state.frame_since_control_start += ojama_frame;
+
+        Self::with_chain(new_player_state, chain, &new_placements, table)
+    }
+}
+
+/// Fire the chain if any, and offset the pending ojama with it.
+fn simulate(player_state: &mut DetailedPlayerState) -> Chain {
+    let chain = player_state.board.simulate();
+    player_state.frame += chain.frame();
+    player_state.frame_since_control_start += chain.frame();
+    player_state.frame_by_chain += chain.frame();
+    if chain.chain() > 0 {
+        // pending zenkeshi bonus is consumed by firing
+        player_state.send_ojama(chain.score());
+        player_state.zenkeshi = chain.zenkeshi();
     }
+    chain
+}
+
+/// Hash of `player_state` except for the tumos (which are the same at the same depth) and
+/// the score. Nodes with the same hash have the same eval and the same subtree.
+fn state_hash(player_state: &DetailedPlayerState, board_hash: u64) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (
+        board_hash,
+        player_state.frame,
+        player_state.frame_since_control_start,
+        player_state.frame_by_chain,
+        player_state.frame_by_chigiri,
+        player_state.carry_over,
+        player_state.ojama_fixed,
+        player_state.ojama_incoming,
+        player_state.current_chain,
+        player_state.zenkeshi,
+    )
+        .hash(&mut hasher);
+    hasher.finish()
 }
 
-pub(super) fn sort_by_eval(nodes: &mut Vec<Node>) {
+fn sort_by_eval(nodes: &mut Vec<Node>) {
     nodes.sort_by(|a, b| b.eval_score.cmp(&a.eval_score))
 }
+
+/// Sort by eval, and keep only the best node for each state.
+pub(super) fn sort_and_dedup_by_eval(nodes: &mut Vec<Node>) {
+    sort_by_eval(nodes);
+
+    let mut seen = HashSet::with_capacity(nodes.len());
+    nodes.retain(|node| seen.insert(node.hash));
+}
+
+/// Decision toward the first node of `nodes` sorted by eval.
+pub(in crate::searcher) fn best_decision(nodes: &[Node], evaluator: &Evaluator) -> Decision {
+    let Some(best) = nodes.first().filter(|node| !node.placements.is_empty()) else {
+        return Decision {
+            placements: vec![Placement::new(3, 0)],
+            chain: Chain::default(),
+            logging: Some("muri...".to_owned()),
+        };
+    };
+
+    Decision {
+        placements: best.placements.clone(),
+        chain: best.chain.clone(),
+        logging: Some(format!(
+            "eval: {:>6}\ntactics: {:>7}",
+            best.eval_score, evaluator.name
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{
+        board::{Board, WIDTH},
+        color::PuyoColor::*,
+        tumo::Tumos,
+    };
+
+    use super::*;
+    use crate::evaluator::BUILD;
+
+    fn node(board: Board, ojama_fixed: u32, table: &mut TranspositionTable) -> Node {
+        let player_state = PlayerState::new(board, Tumos::default(), 0, 0, 0, ojama_fixed, 0, 0);
+        Node::from_player_state(&player_state, &[], table)
+    }
+
+    #[test]
+    fn drop_fixed_ojama_after_placing() {
+        let mut table = TranspositionTable::new(&BUILD);
+        let root = node(Board::new(), 40, &mut table);
+        assert_eq!(root.player_state.board, Board::new());
+
+        let tumo = Tumo::new(RED, BLUE);
+        let node = root.place_tumo(&tumo, &Placement::new(1, 0), &mut table);
+        assert_eq!(
+            node.player_state.board.height_array()[1..=WIDTH],
+            [7, 5, 5, 5, 5, 5]
+        );
+        assert_eq!(node.player_state.ojama_fixed, 10);
+
+        let node = node.place_tumo(&tumo, &Placement::new(6, 0), &mut table);
+        assert_eq!(
+            node.player_state.board.height_array()[1..=WIDTH],
+            [8, 7, 7, 7, 7, 8]
+        );
+        assert_eq!(node.player_state.ojama_fixed, 0);
+    }
+
+    #[test]
+    fn offset_fixed_ojama_by_firing() {
+        let mut table = TranspositionTable::new(&BUILD);
+        // 2 chain sending 6 ojamas
+        let board = Board::from(concat!(
+            "....B.", // 4
+            "....RB", // 3
+            "....RB", // 2
+            "....RB", // 1
+        ));
+        let root = node(board, 8, &mut table);
+
+        let node = root.place_tumo(&Tumo::new(RED, RED), &Placement::new(4, 0), &mut table);
+        assert_eq!(node.chain.chain(), 2);
+        // the 2 ojamas left drop after the chain
+        assert_eq!(node.player_state.ojama_fixed, 0);
+        assert_eq!(node.player_state.board, Board::from("..oo.."));
+    }
+
+    #[test]
+    fn dedup_by_state() {
+        let mut table = TranspositionTable::new(&BUILD);
+        let board: Board = [2, 2, 2, 2, 2, 2].into();
+        let mut nodes = vec![
+            node(board.clone(), 0, &mut table),
+            node(board.clone(), 0, &mut table),
+            node(board.clone(), 12, &mut table),
+        ];
+        nodes[1].player_state.zenkeshi = true;
+        nodes[1].hash = state_hash(&nodes[1].player_state, board.zobrist_hash());
+
+        // the same board with different pending ojama or zenkeshi is a different state
+        sort_and_dedup_by_eval(&mut nodes);
+        assert_eq!(nodes.len(), 3);
+
+        nodes.push(node(board, 12, &mut table));
+        sort_and_dedup_by_eval(&mut nodes);
+        assert_eq!(nodes.len(), 3);
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::{hash_map::Entry, HashMap};
+
+use crate::{evaluator::Evaluator, DetailedPlayerState};
+
+/// Transposition table used within a single search.
+///
+/// Positions are identified by [Node::hash](super::node::Node::hash), while board scores are
+/// cached by the zobrist hash of the board.
+pub(in crate::searcher) struct TranspositionTable {
+    pub evaluator: Evaluator,
+    /// (hash, depth) -> the best eval score reached so far
+    best: HashMap<(u64, usize), i32>,
+    /// board hash -> [Evaluator::evaluate_board]
+    board_scores: HashMap<u64, i32>,
+}
+
+impl TranspositionTable {
+    pub fn new(evaluator: &Evaluator) -> Self {
+        Self {
+            evaluator: *evaluator,
+            best: HashMap::new(),
+            board_scores: HashMap::new(),
+        }
+    }
+
+    /// Forget visited positions to start a new search, while keeping cached evaluations.
+    pub fn clear_visits(&mut self) {
+        self.best.clear();
+    }
+
+    /// Same as [Evaluator::evaluate], but the board part is cached by `board_hash`.
+    pub fn evaluate(&mut self, player_state: &DetailedPlayerState, board_hash: u64) -> i32 {
+        let evaluator = &self.evaluator;
+        let board_score = *self
+            .board_scores
+            .entry(board_hash)
+            .or_insert_with(|| evaluator.evaluate_board(&player_state.board));
+
+        evaluator.evaluate_with_board_score(player_state, board_score)
+    }
+
+    /// Record the position reached at `depth` with `eval_score`.
+    /// Return false if the same position has already been reached with an equal or better score.
+    pub fn visit(&mut self, hash: u64, depth: usize, eval_score: i32) -> bool {
+        match self.best.entry((hash, depth)) {
+            Entry::Occupied(mut entry) => {
+                if *entry.get() >= eval_score {
+                    return false;
+                }
+                entry.insert(eval_score);
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(eval_score);
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{board::Board, player_state::PlayerState, tumo::Tumos};
+
+    use super::*;
+    use crate::evaluator::BUILD;
+
+    #[test]
+    fn evaluate_same_as_evaluator() {
+        let board = Board::from(concat!(
+            "RY....", // 3
+            "RBBG..", // 2
+            "YBGG..", // 1
+        ));
+        let mut player_state: DetailedPlayerState =
+            PlayerState::new(board, Tumos::default(), 0, 0, 0, 0, 0, 0).into();
+        let hash = player_state.board.zobrist_hash();
+        let mut table = TranspositionTable::new(&BUILD);
+
+        assert_eq!(
+            table.evaluate(&player_state, hash),
+            BUILD.evaluate(&player_state)
+        );
+
+        // cached board score is reused with different frames
+        player_state.frame_by_chigiri = 20;
+        assert_eq!(
+            table.evaluate(&player_state, hash),
+            BUILD.evaluate(&player_state)
+        );
+    }
+
+    #[test]
+    fn visit_keeps_best() {
+        let mut table = TranspositionTable::new(&BUILD);
+
+        assert!(table.visit(1, 0, 10));
+        assert!(!table.visit(1, 0, 10));
+        assert!(!table.visit(1, 0, 5));
+        assert!(table.visit(1, 0, 15));
+        assert!(table.visit(1, 1, 5));
+        assert!(table.visit(2, 0, 5));
+
+        table.clear_visits();
+        assert!(table.visit(1, 0, 10));
+    }
+}
@@ -0,0 +1,203 @@
+use core::{board::WIDTH, placement::Placement, player_state::PlayerState, tumo::Tumo};
+
+use super::{
+    beam_search::{
+        node::{best_decision, Node},
+        transposition_table::TranspositionTable,
+    },
+    SearchLimit, Searcher,
+};
+use crate::{decision::Decision, evaluator::Evaluator};
+
+/// Enumerate every placement of the visible tumos depth-first, and pick the best leaf.
+/// Subtrees whose [Evaluator::upper_bound] cannot beat the best leaf so far are pruned,
+/// so the result is exact unless the limit is reached.
+pub struct DfsSearcher;
+
+impl DfsSearcher {
+    /// Deeper searches are too slow to be exhaustive.
+    pub const MAX_DEPTH: usize = 3;
+}
+
+impl Searcher for DfsSearcher {
+    fn search(
+        &self,
+        player_state: &PlayerState,
+        evaluator: &Evaluator,
+        limit: &SearchLimit,
+    ) -> Decision {
+        let mut table = TranspositionTable::new(evaluator);
+        let root = Node::from_player_state(player_state, &[], &mut table);
+
+        let depth = Self::MAX_DEPTH.min(player_state.tumos.available_tumo_len());
+        let tumos: Vec<Tumo> = (0..depth).map(|i| player_state.tumos[i]).collect();
+        let mut dfs = Dfs {
+            tumos: &tumos,
+            table,
+            limit,
+            best: None,
+        };
+        dfs.search(&root);
+
+        best_decision(dfs.best.as_slice(), evaluator)
+    }
+}
+
+struct Dfs<'a> {
+    tumos: &'a [Tumo],
+    table: TranspositionTable,
+    limit: &'a SearchLimit,
+    /// Deepest node with the best eval score so far.
+    best: Option<Node>,
+}
+
+impl Dfs<'_> {
+    fn search(&mut self, node: &Node) {
+        let depth = node.placements.len();
+        if self.is_better(node) {
+            self.best = Some(node.clone());
+        }
+        if depth == self.tumos.len() || self.limit.should_stop() || self.is_hopeless(node) {
+            return;
+        }
+
+        let tumo = &self.tumos[depth];
+        let placements_itr = if tumo.is_zoro() {
+            Placement::placements_zoro().iter()
+        } else {
+            Placement::placements_non_zoro().iter()
+        };
+
+        for placement in placements_itr {
+            if !node.player_state.board.is_placeable(placement) {
+                continue;
+            }
+
+            let nxt = node.place_tumo(tumo, placement, &mut self.table);
+            if nxt.player_state.board.is_dead() {
+                continue;
+            }
+            // the same state at the same depth leads to the same subtree
+            if !self.table.visit(nxt.hash, depth, nxt.eval_score) {
+                continue;
+            }
+
+            self.search(&nxt);
+        }
+    }
+
+    /// Deeper nodes are preferred, so that a dead end is taken only if every line dies.
+    fn is_better(&self, node: &Node) -> bool {
+        self.best.as_ref().map_or(true, |best| {
+            (node.placements.len(), node.eval_score) > (best.placements.len(), best.eval_score)
+        })
+    }
+
+    /// True if no leaf under `node` can beat the best leaf found so far.
+    fn is_hopeless(&self, node: &Node) -> bool {
+        let Some(best) = self
+            .best
+            .as_ref()
+            .filter(|b| b.placements.len() == self.tumos.len())
+        else {
+            return false;
+        };
+
+        let remaining = self.tumos.len() - node.placements.len();
+        let puyos = node.player_state.board.height_array()[1..=WIDTH]
+            .iter()
+            .sum::<usize>()
+            + 2 * remaining;
+
+        self.table
+            .evaluator
+            .upper_bound(&node.player_state, puyos)
+            .is_some_and(|bound| bound <= best.eval_score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{board::Board, color::PuyoColor::*, tumo::Tumos};
+
+    use super::*;
+    use crate::evaluator::{BUILD, BUILD_ENDGAME, ZENKESHI};
+
+    /// Best eval score among all the leaves, without any pruning.
+    fn brute_force(node: &Node, tumos: &[Tumo], table: &mut TranspositionTable) -> i32 {
+        let Some((tumo, rest)) = tumos.split_first() else {
+            return node.eval_score;
+        };
+
+        let placements = if tumo.is_zoro() {
+            Placement::placements_zoro().as_slice()
+        } else {
+            Placement::placements_non_zoro().as_slice()
+        };
+
+        let mut best = i32::MIN;
+        for placement in placements {
+            if !node.player_state.board.is_placeable(placement) {
+                continue;
+            }
+            let nxt = node.place_tumo(tumo, placement, table);
+            if !nxt.player_state.board.is_dead() {
+                best = best.max(brute_force(&nxt, rest, table));
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn same_as_brute_force() {
+        let board = Board::from(concat!(
+            "R.....", // 4
+            "RY....", // 3
+            "RBBG..", // 2
+            "YBGG..", // 1
+        ));
+        let tumos = [Tumo::new(BLUE, GREEN), Tumo::new(YELLOW, YELLOW)];
+        // fixed ojama drops unless it fires, so the state is more than the board
+        let player_state = PlayerState::new(board, Tumos::new(&tumos), 0, 0, 0, 6, 0, 0);
+
+        for evaluator in [BUILD, BUILD_ENDGAME, ZENKESHI] {
+            let decision = DfsSearcher.search(&player_state, &evaluator, &SearchLimit::default());
+            assert_eq!(decision.placements.len(), 2);
+
+            let mut table = TranspositionTable::new(&evaluator);
+            let root = Node::from_player_state(&player_state, &[], &mut table);
+            let node = decision
+                .placements
+                .iter()
+                .zip(&tumos)
+                .fold(root.clone(), |node, (placement, tumo)| {
+                    node.place_tumo(tumo, placement, &mut table)
+                });
+            assert_eq!(node.eval_score, brute_force(&root, &tumos, &mut table));
+        }
+    }
+
+    #[test]
+    fn search_returns_valid_placement() {
+        let boards: [Board; 5] = [
+            [0, 0, 0, 0, 0, 0].into(),
+            [11, 11, 11, 11, 11, 11].into(),
+            [12, 12, 11, 12, 12, 12].into(),
+            [11, 13, 11, 11, 11, 11].into(),
+            [11, 13, 11, 13, 11, 11].into(),
+        ];
+        let tumos = Tumos::new(&[
+            Tumo::new(RED, GREEN),
+            Tumo::new_zoro(BLUE),
+            Tumo::new(YELLOW, RED),
+        ]);
+
+        for board in &boards {
+            let player_state = PlayerState::new(board.clone(), tumos.clone(), 0, 0, 0, 0, 0, 0);
+            let decision = DfsSearcher.search(&player_state, &BUILD, &SearchLimit::default());
+
+            assert!(!decision.placements.is_empty());
+            assert!(board.is_placeable(decision.placements.first().unwrap()));
+        }
+    }
+}
@@ -2,16 +2,17 @@ use core::{board::Board, placement::Placement, player_state::PlayerState};
 
 use rand::seq::SliceRandom;
 
-use super::Searcher;
+use super::{SearchLimit, Searcher};
 use crate::{decision::Decision, evaluator::Evaluator};
 
 pub struct RandomSearcher;
 
 impl Searcher for RandomSearcher {
     fn search(
+        &self,
         player_state: &PlayerState,
         _evaluator: &Evaluator,
-        _think_frame: Option<u32>,
+        _limit: &SearchLimit,
     ) -> Decision {
         let placement =
             Self::random_valid_placement(&player_state.board, player_state.tumos[0].is_zoro());
@@ -61,7 +62,8 @@ mod tests {
         for board in &boards {
             for tumos in &tumos_pattern {
                 let player_state = PlayerState::new(board.clone(), tumos.clone(), 0, 0, 0, 0, 0, 0);
-                let decision = RandomSearcher::search(&player_state, &BUILD, None);
+                let decision =
+                    RandomSearcher.search(&player_state, &BUILD, &SearchLimit::default());
 
                 assert!(!decision.placements.is_empty());
                 assert!(board.is_placeable(decision.placements.first().unwrap()));
@@ -0,0 +1,84 @@
+use core::ojama::FPS;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// When to stop searching.
+///
+/// The search stops at `deadline` or when `stop` is set, whichever comes first.
+/// Without both of them, searchers run a small fixed-size search (e.g. for SPSA).
+#[derive(Clone, Default, Debug)]
+pub struct SearchLimit {
+    pub deadline: Option<Instant>,
+    pub stop: Option<Arc<AtomicBool>>,
+}
+
+impl SearchLimit {
+    pub fn with_deadline(deadline: Instant) -> Self {
+        Self {
+            deadline: Some(deadline),
+            stop: None,
+        }
+    }
+
+    /// Deadline after `millis` from `start`, leaving 10% of it to return the decision.
+    pub fn from_millis(start: Instant, millis: u64) -> Self {
+        Self::with_deadline(start + Duration::from_millis(millis * 9 / 10))
+    }
+
+    pub fn from_think_frame(start: Instant, think_frame: Option<u32>) -> Self {
+        match think_frame {
+            Some(frame) => Self::from_millis(start, frame as u64 * 1000 / FPS as u64),
+            None => Self::default(),
+        }
+    }
+
+    pub fn with_stop(self, stop: Arc<AtomicBool>) -> Self {
+        Self {
+            stop: Some(stop),
+            ..self
+        }
+    }
+
+    /// False if the search should be done with the fixed size.
+    pub fn is_bounded(&self) -> bool {
+        self.deadline.is_some() || self.stop.is_some()
+    }
+
+    pub fn should_stop(&self) -> bool {
+        if let Some(stop) = &self.stop {
+            if stop.load(Ordering::Relaxed) {
+                return true;
+            }
+        }
+
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_stop() {
+        let start = Instant::now();
+
+        assert!(!SearchLimit::default().is_bounded());
+        assert!(!SearchLimit::default().should_stop());
+        assert!(SearchLimit::with_deadline(start).should_stop());
+        assert!(!SearchLimit::from_millis(start, 60_000).should_stop());
+        assert!(!SearchLimit::from_think_frame(start, None).is_bounded());
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let limit = SearchLimit::from_millis(start, 60_000).with_stop(stop.clone());
+        assert!(!limit.should_stop());
+        stop.store(true, Ordering::Relaxed);
+        assert!(limit.should_stop());
+    }
+}
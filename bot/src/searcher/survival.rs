@@ -0,0 +1,197 @@
+use core::{
+    board::{HEIGHT, WIDTH},
+    chain::Chain,
+    placement::Placement,
+    player_state::PlayerState,
+};
+use std::cmp::Reverse;
+
+use super::{SearchLimit, Searcher};
+use crate::{decision::Decision, evaluator::Evaluator};
+
+/// Searches only for the placement which keeps Nova alive the longest.
+/// Used as a safety net when other searchers or chain pickers choose a fatal placement.
+pub struct SurvivalSearcher;
+
+impl Searcher for SurvivalSearcher {
+    fn search(
+        &self,
+        player_state: &PlayerState,
+        _evaluator: &Evaluator,
+        _limit: &SearchLimit,
+    ) -> Decision {
+        Self::survival_decision(player_state).unwrap_or_else(Decision::fallback)
+    }
+}
+
+impl SurvivalSearcher {
+    /// Number of tumos looked ahead.
+    pub const DEPTH: usize = 2;
+
+    /// True if placing the first tumo with `placement` kills the player,
+    /// including the fixed ojama dropped after it.
+    pub fn is_fatal(player_state: &PlayerState, placement: &Placement) -> bool {
+        place(player_state, 0, placement).map_or(true, |ps| ps.board.is_dead())
+    }
+
+    /// Placement of the first tumo surviving the most tumos within [SurvivalSearcher::DEPTH].
+    /// Ties are broken by lower 3rd column and then more empty cells.
+    /// None if every placement is fatal.
+    pub fn survival_placement(player_state: &PlayerState) -> Option<Placement> {
+        let depth = Self::DEPTH.min(player_state.tumos.available_tumo_len());
+        if depth == 0 {
+            return None;
+        }
+
+        player_state
+            .board
+            .valid_placements(player_state.tumos[0].is_zoro())
+            .into_iter()
+            .filter_map(|placement| Some((placement, place(player_state, 0, placement)?)))
+            .filter(|(_, ps)| !ps.board.is_dead())
+            .max_by_key(|(_, ps)| survival_score(ps, 1, depth))
+            .map(|(placement, _)| *placement)
+    }
+
+    /// Decision toward [SurvivalSearcher::survival_placement].
+    pub fn survival_decision(player_state: &PlayerState) -> Option<Decision> {
+        Some(Decision {
+            placements: vec![Self::survival_placement(player_state)?],
+            chain: Chain::default(),
+            logging: Some("survive".to_owned()),
+        })
+    }
+}
+
+/// (survived tumos, lower 3rd column, empty cells) of the best line from `player_state`.
+fn survival_score(
+    player_state: &PlayerState,
+    index: usize,
+    depth: usize,
+) -> (usize, Reverse<usize>, usize) {
+    let heights = player_state.board.height_array();
+    let empty_cells = heights[1..=WIDTH]
+        .iter()
+        .map(|&h| HEIGHT.saturating_sub(h))
+        .sum();
+    let leaf = (index, Reverse(heights[3]), empty_cells);
+    if index >= depth {
+        return leaf;
+    }
+
+    player_state
+        .board
+        .valid_placements(player_state.tumos[index].is_zoro())
+        .into_iter()
+        .filter_map(|placement| place(player_state, index, placement))
+        .filter(|ps| !ps.board.is_dead())
+        .map(|ps| survival_score(&ps, index + 1, depth))
+        .max()
+        .unwrap_or(leaf)
+}
+
+/// Place the `index`-th tumo, fire the chain if any, and drop the fixed ojama left.
+fn place(player_state: &PlayerState, index: usize, placement: &Placement) -> Option<PlayerState> {
+    let mut player_state = player_state.clone();
+    player_state
+        .board
+        .place_tumo(&player_state.tumos[index], placement)?;
+
+    let chain = player_state.board.simulate();
+    if chain.chain() > 0 {
+        player_state.send_ojama(chain.score());
+    }
+    player_state.drop_fixed_ojama_conservatively();
+
+    Some(player_state)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{
+        board::Board,
+        color::PuyoColor::*,
+        tumo::{Tumo, Tumos},
+    };
+
+    use super::*;
+
+    #[test]
+    fn avoid_fatal_placement() {
+        let board = Board::from(concat!(
+            "..Y...", // 11
+            "..G...", // 10
+            "..B...", // 9
+            "..Y...", // 8
+            "..G...", // 7
+            "..B...", // 6
+            "..Y...", // 5
+            "..G...", // 4
+            "..B...", // 3
+            "..Y...", // 2
+            "..G...", // 1
+        ));
+        let tumos = Tumos::new(&[Tumo::new(RED, GREEN), Tumo::new(RED, GREEN)]);
+        let player_state = PlayerState::new(board, tumos, 0, 0, 0, 0, 0, 0);
+
+        assert!(SurvivalSearcher::is_fatal(
+            &player_state,
+            &Placement::new(3, 0)
+        ));
+        let placement = SurvivalSearcher::survival_placement(&player_state).unwrap();
+        assert!(!SurvivalSearcher::is_fatal(&player_state, &placement));
+        assert_ne!(placement.axis_x(), 3);
+    }
+
+    #[test]
+    fn fatal_by_fixed_ojama() {
+        let board = Board::from(concat!(
+            "..Y...", // 6
+            "..G...", // 5
+            "..B...", // 4
+            "..Y...", // 3
+            "..G...", // 2
+            "..B...", // 1
+        ));
+        let tumos = Tumos::new(&[Tumo::new(RED, GREEN)]);
+        // 5 rows of ojama fill the 3rd column after placing on it
+        let player_state = PlayerState::new(board, tumos, 0, 0, 0, 30, 0, 0);
+
+        assert!(SurvivalSearcher::is_fatal(
+            &player_state,
+            &Placement::new(3, 0)
+        ));
+        assert!(!SurvivalSearcher::is_fatal(
+            &player_state,
+            &Placement::new(1, 0)
+        ));
+        assert!(SurvivalSearcher::survival_placement(&player_state).is_some());
+    }
+
+    #[test]
+    fn fatal_after_partial_offset() {
+        let board = Board::from(concat!(
+            "..Y...", // 11
+            "..G...", // 10
+            "..B...", // 9
+            "..Y...", // 8
+            "..G...", // 7
+            "..B...", // 6
+            "..Y...", // 5
+            "..G.B.", // 4
+            "..B.RB", // 3
+            "..Y.RB", // 2
+            "..G.RB", // 1
+        ));
+        let tumos = Tumos::new(&[Tumo::new(RED, RED)]);
+        // 2 chain offsetting 6 ojamas
+        let fire = Placement::new(4, 0);
+
+        let player_state = PlayerState::new(board.clone(), tumos.clone(), 0, 0, 0, 6, 0, 0);
+        assert!(!SurvivalSearcher::is_fatal(&player_state, &fire));
+
+        // a row of ojama is left and fills the 3rd column
+        let player_state = PlayerState::new(board, tumos, 0, 0, 0, 12, 0, 0);
+        assert!(SurvivalSearcher::is_fatal(&player_state, &fire));
+    }
+}
@@ -0,0 +1,125 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Arc, Mutex},
+    thread::{self, JoinHandle},
+};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Fixed number of threads which are kept alive across searches.
+pub struct WorkerPool {
+    sender: Option<mpsc::Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl WorkerPool {
+    pub fn new(threads: usize) -> Self {
+        debug_assert!(threads > 0);
+
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let workers = (0..threads.max(1))
+            .map(|_| {
+                let receiver = Arc::clone(&receiver);
+                thread::spawn(move || loop {
+                    // release the lock before running the job
+                    let job = receiver.lock().unwrap().recv();
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => break,
+                    }
+                })
+            })
+            .collect();
+
+        Self {
+            sender: Some(sender),
+            workers,
+        }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Run `jobs` on the pool, and return their results in the order of completion.
+    /// If a job panics, the panic is resumed here after all jobs finish, and the worker survives.
+    pub fn run<T, F>(&self, jobs: Vec<F>) -> Vec<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let n = jobs.len();
+        let (result_sender, result_receiver) = mpsc::channel();
+
+        for job in jobs {
+            let result_sender = result_sender.clone();
+            self.sender
+                .as_ref()
+                .unwrap()
+                .send(Box::new(move || {
+                    let result = panic::catch_unwind(AssertUnwindSafe(job));
+                    result_sender.send(result).ok();
+                }))
+                .unwrap();
+        }
+        drop(result_sender);
+
+        let results: Vec<thread::Result<T>> = result_receiver.iter().take(n).collect();
+        results
+            .into_iter()
+            .map(|result| result.unwrap_or_else(|payload| panic::resume_unwind(payload)))
+            .collect()
+    }
+}
+
+impl Default for WorkerPool {
+    /// As many threads as the available parallelism.
+    fn default() -> Self {
+        Self::new(thread::available_parallelism().map_or(1, |n| n.get()))
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        // workers exit once the channel is closed
+        drop(self.sender.take());
+        for worker in self.workers.drain(..) {
+            worker.join().ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_all_jobs() {
+        let pool = WorkerPool::new(3);
+        assert_eq!(pool.threads(), 3);
+
+        for _ in 0..2 {
+            let jobs = (0..10).map(|i| move || i * i).collect();
+            let mut results = pool.run(jobs);
+            results.sort();
+
+            assert_eq!(results, (0..10).map(|i| i * i).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn propagate_panic() {
+        let pool = WorkerPool::new(1);
+
+        let jobs: Vec<Box<dyn FnOnce() -> i32 + Send>> =
+            vec![Box::new(|| 1), Box::new(|| panic!("job failed"))];
+        let result = panic::catch_unwind(AssertUnwindSafe(|| pool.run(jobs)));
+        assert!(result.is_err());
+
+        // the only worker is still alive
+        let jobs = (0..4).map(|i| move || i).collect();
+        assert_eq!(pool.run(jobs).len(), 4);
+    }
+}
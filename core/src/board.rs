@@ -1,9 +1,12 @@
 mod board;
 mod boardbits;
+mod chain_steps;
+mod zobrist;
 
 pub use self::{
     board::Board,
     boardbits::{BoardBits, BoardOps},
+    chain_steps::{ChainStep, ChainSteps},
 };
 
 /// Board width.
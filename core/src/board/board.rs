@@ -10,8 +10,12 @@ use crate::{
     color::PuyoColor,
 };
 
-#[derive(Clone, PartialEq)]
-pub struct Board(BoardBits, BoardBits, BoardBits);
+#[derive(Clone, PartialEq, Eq)]
+pub struct Board(
+    pub(super) BoardBits,
+    pub(super) BoardBits,
+    pub(super) BoardBits,
+);
 
 impl Board {
     pub fn new() -> Self {
@@ -113,6 +117,19 @@ impl Board {
 
     /// Return (BoardBits of popping_puyos, num_popped_puyos, color_bonus, conn_bonus)
     pub fn popping_puyos(&self) -> Option<(BoardBits, u32, u32, u32)> {
+        self.popping_puyos_with_num_colors()
+            .map(|(popped, num, num_colors, conn_bonus)| {
+                (
+                    popped,
+                    num,
+                    score::color_bonus(num_colors as usize),
+                    conn_bonus,
+                )
+            })
+    }
+
+    /// Return (BoardBits of popping_puyos, num_popped_puyos, num_colors, conn_bonus)
+    pub(super) fn popping_puyos_with_num_colors(&self) -> Option<(BoardBits, u32, u32, u32)> {
         // 0b10x = RED (0b100) or GREEN (0b101)
         let b12_01 = self.1.andnot(self.2).mask_12();
         // 0b11x = BLUE (0b110) or YELLOW (0b111)
@@ -155,8 +172,6 @@ impl Board {
             return None;
         }
 
-        let color_bonus = score::color_bonus(num_colors);
-
         // remove ojama adjacent to `popped_puyos`
         popped_puyos = popped_puyos
             | popped_puyos
@@ -166,7 +181,7 @@ impl Board {
         Some((
             popped_puyos,
             num_popped_puyos as u32,
-            color_bonus,
+            num_colors,
             conn_bonus,
         ))
     }
@@ -260,8 +275,10 @@ impl Board {
             self.pop_and_apply_gravity(popped_puyos);
         }
 
+        // zenkeshi is judged before the puyos above the 13th row come back
+        let zenkeshi = chain >= initial_chain && self.is_zenkeshi();
         self.unescape_above_13th_row(&escaped);
-        Chain::new(chain, score * 10, frame)
+        Chain::new(chain, score * 10, frame).with_zenkeshi(zenkeshi)
     }
 
     pub fn place_puyo(&mut self, x: usize, c: PuyoColor) {
@@ -271,6 +288,11 @@ impl Board {
         self.set(x, y_ + 1, c)
     }
 
+    /// True if there's no puyo up to the 13th row.
+    pub fn is_zenkeshi(&self) -> bool {
+        self.0.mask_13().is_zero() && self.1.mask_13().is_zero() && self.2.mask_13().is_zero()
+    }
+
     pub fn is_dead(&self) -> bool {
         self.get(3, HEIGHT) != PuyoColor::EMPTY
     }
@@ -775,6 +797,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn is_zenkeshi() {
+        assert!(Board::new().is_zenkeshi());
+        assert!(!Board::from("..R...").is_zenkeshi());
+        assert!(!Board::from(concat!(
+            "..O...", // 13
+            "......", // 12
+            "......", // 11
+            "......", // 10
+            "......", // 9
+            "......", // 8
+            "......", // 7
+            "......", // 6
+            "......", // 5
+            "......", // 4
+            "......", // 3
+            "......", // 2
+            "......"  // 1
+        ))
+        .is_zenkeshi());
+    }
+
     #[test]
     fn simulate() {
         let board_and_chain = [
@@ -811,7 +855,8 @@ mod tests {
                         + frame::chain_frames(2)
                         + frame::chain_frames(1)
                         + frame::chain_frames(0),
-                ),
+                )
+                .with_zenkeshi(true),
             ),
             (
                 Board::from(concat!(
@@ -843,7 +888,7 @@ mod tests {
                     "RBGBGG"  // 1
                 )),
                 // Note: haven't checked if frame is correct or not
-                Chain::new(19, 175080, 1551),
+                Chain::new(19, 175080, 1551).with_zenkeshi(true),
             ),
         ];
 
@@ -858,7 +903,7 @@ mod tests {
             (
                 Board::from(".BBBB."),
                 3,
-                Chain::new(3, 40 * 16, frame::chain_frames(0)),
+                Chain::new(3, 40 * 16, frame::chain_frames(0)).with_zenkeshi(true),
             ),
             (
                 Board::from(concat!(
@@ -876,7 +921,8 @@ mod tests {
                         + frame::chain_frames(3)
                         + frame::chain_frames(3)
                         + frame::chain_frames(0),
-                ),
+                )
+                .with_zenkeshi(true),
             ),
             (
                 Board::from(concat!(
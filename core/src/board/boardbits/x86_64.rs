@@ -117,6 +117,8 @@ impl PartialEq<Self> for BoardBits {
     }
 }
 
+impl Eq for BoardBits {}
+
 impl std::ops::BitAnd for BoardBits {
     type Output = Self;
 
@@ -0,0 +1,218 @@
+use super::{Board, BoardBits};
+use crate::chain::{frame, score};
+
+/// Details of one step (= one pop) of a chain.
+#[derive(Clone, Debug)]
+pub struct ChainStep {
+    /// n-th chain
+    pub chain: u32,
+    pub popped_puyos: BoardBits,
+    pub num_popped_puyos: u32,
+    pub num_colors: u32,
+    pub conn_bonus: u32,
+    pub score: u32,
+    pub max_drops: u16,
+    pub frame: u32,
+    /// Board after popping and applying gravity.
+    pub board: Board,
+}
+
+/// Iterator yielding each step of a chain. See [Board::chain_steps].
+#[derive(Clone)]
+pub struct ChainSteps {
+    board: Board,
+    escaped: Board,
+    chain: u32,
+}
+
+impl ChainSteps {
+    fn new(board: &Board, initial_chain: u32) -> Self {
+        let mut board = board.clone();
+        let escaped = board.escape_above_13th_row();
+
+        Self {
+            board,
+            escaped,
+            chain: initial_chain - 1,
+        }
+    }
+
+    /// The last chain yielded so far.
+    pub const fn last_chain(&self) -> u32 {
+        self.chain
+    }
+
+    /// Current board, including puyos above the 13th row.
+    pub fn board(&self) -> Board {
+        let mut board = self.board.clone();
+        board.unescape_above_13th_row(&self.escaped);
+        board
+    }
+
+    /// True if no puyo is left up to the 13th row.
+    pub fn is_zenkeshi(&self) -> bool {
+        self.board.is_zenkeshi()
+    }
+}
+
+impl Iterator for ChainSteps {
+    type Item = ChainStep;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (popped_puyos, num_popped_puyos, num_colors, conn_bonus) =
+            self.board.popping_puyos_with_num_colors()?;
+
+        self.chain += 1;
+        let chain_bonus = score::chain_bonus(self.chain as usize);
+        let color_bonus = score::color_bonus(num_colors as usize);
+        let score = num_popped_puyos * (chain_bonus + color_bonus + conn_bonus).clamp(1, 999) * 10;
+        let max_drops = self.board.max_drops(popped_puyos);
+
+        self.board.pop_and_apply_gravity(popped_puyos);
+
+        Some(ChainStep {
+            chain: self.chain,
+            popped_puyos,
+            num_popped_puyos,
+            num_colors,
+            conn_bonus,
+            score,
+            max_drops,
+            frame: frame::chain_frames(max_drops),
+            board: self.board(),
+        })
+    }
+}
+
+impl Board {
+    /// Simulate a chain step by step, starting from `initial_chain`-th chain.
+    /// Use [Board::simulate_from_middle] if only the result is needed.
+    pub fn chain_steps(&self, initial_chain: u32) -> ChainSteps {
+        ChainSteps::new(self, initial_chain)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chain::Chain, color::PuyoColor};
+
+    #[test]
+    fn chain_steps() {
+        let board = Board::from(concat!(
+            "RY....", // 5
+            "RY....", // 4
+            "RBBG..", // 3
+            "YBGG..", // 2
+            "RYBG..", // 1
+        ));
+
+        let steps = board.chain_steps(1).collect::<Vec<_>>();
+
+        assert_eq!(steps.len(), 4);
+        assert_eq!(
+            steps.iter().map(|s| s.chain).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+        assert_eq!(
+            steps.iter().map(|s| s.score).collect::<Vec<_>>(),
+            vec![40, 40 * 8, 40 * 16, 40 * 32]
+        );
+        assert_eq!(
+            steps.iter().map(|s| s.max_drops).collect::<Vec<_>>(),
+            vec![1, 2, 1, 0]
+        );
+        assert!(steps.iter().all(|s| s.num_popped_puyos == 4));
+        assert!(steps.iter().all(|s| s.num_colors == 1));
+        assert!(steps.iter().all(|s| s.conn_bonus == 0));
+
+        let mut expected = board.clone();
+        let escaped = expected.escape_above_13th_row();
+        for step in &steps {
+            let (popped_puyos, ..) = expected.popping_puyos().unwrap();
+            expected.pop_and_apply_gravity(popped_puyos);
+
+            let mut expected = expected.clone();
+            expected.unescape_above_13th_row(&escaped);
+            assert_eq!(step.board, expected);
+        }
+        assert_eq!(steps[3].board, Board::new());
+    }
+
+    #[test]
+    fn chain_steps_keep_14th_row() {
+        let mut board = Board::from(concat!(
+            "..R...", // 3
+            "..R...", // 2
+            "..RR..", // 1
+        ));
+        board.set(3, 14, PuyoColor::BLUE);
+
+        let mut steps = board.chain_steps(1);
+        let step = steps.next().unwrap();
+
+        assert!(steps.next().is_none());
+        assert!(steps.is_zenkeshi());
+        assert_eq!(step.board.get(3, 14), PuyoColor::BLUE);
+        assert_eq!(steps.board(), step.board);
+    }
+
+    #[test]
+    fn same_totals_as_simulate() {
+        let boards_and_initial_chains = [
+            (
+                Board::from(concat!(
+                    ".G.BRG", // 13
+                    "GBRRYR", // 12
+                    "RRYYBY", // 11
+                    "RGYRBR", // 10
+                    "YGYRBY", // 9
+                    "YGBGYR", // 8
+                    "GRBGYR", // 7
+                    "BRBYBY", // 6
+                    "RYYBYY", // 5
+                    "BRBYBR", // 4
+                    "BGBYRR", // 3
+                    "YGBGBG", // 2
+                    "RBGBGG"  // 1
+                )),
+                1,
+            ),
+            (
+                Board::from(concat!(
+                    ".RBRB.", // 4
+                    "RBRBR.", // 3
+                    "RBRBR.", // 2
+                    "RBRBRR"  // 1
+                )),
+                3,
+            ),
+            (
+                Board::from(concat!(
+                    ".YGGY.", // 4
+                    "BBBBBB", // 3
+                    "GYBBYG", // 2
+                    "BBBBBB"  // 1
+                )),
+                2,
+            ),
+        ];
+
+        for (board, max_initial_chain) in boards_and_initial_chains {
+            for initial_chain in 1..=max_initial_chain {
+                let mut steps = board.chain_steps(initial_chain);
+                let (score, frame) = steps.by_ref().fold((0, 0), |(score, frame), step| {
+                    (score + step.score, frame + step.frame)
+                });
+                let total =
+                    Chain::new(steps.last_chain(), score, frame).with_zenkeshi(steps.is_zenkeshi());
+
+                let mut simulated = board.clone();
+                let expected = simulated.simulate_from_middle(initial_chain);
+
+                assert_eq!(total, expected);
+                assert_eq!(steps.board(), simulated);
+            }
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use super::{Board, BoardBits};
+
+/// Random numbers for each bit of each of the three planes of [Board].
+static ZOBRIST_TABLE: [[u64; 128]; 3] = {
+    let mut table = [[0; 128]; 3];
+    // splitmix64
+    let mut seed: u64 = 0x6E6F_7661_7A6F_6272;
+    let mut plane = 0;
+    while plane < 3 {
+        let mut bit = 0;
+        while bit < 128 {
+            seed = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = seed;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            table[plane][bit] = z ^ (z >> 31);
+            bit += 1;
+        }
+        plane += 1;
+    }
+    table
+};
+
+/// XOR of the random numbers of all set bits in `bits`.
+fn plane_hash(bits: BoardBits, plane: usize) -> u64 {
+    let (lo, hi): (u64, u64) = bits.into();
+    let table = &ZOBRIST_TABLE[plane];
+
+    let mut hash = 0;
+    for (mut b, offset) in [(lo, 0), (hi, 64)] {
+        while b != 0 {
+            hash ^= table[offset + b.trailing_zeros() as usize];
+            b &= b - 1;
+        }
+    }
+    hash
+}
+
+impl Board {
+    /// 64-bit zobrist hash of the board.
+    /// It is recomputed from the planes each time, which costs one lookup per set bit.
+    pub fn zobrist_hash(&self) -> u64 {
+        plane_hash(self.0, 0) ^ plane_hash(self.1, 1) ^ plane_hash(self.2, 2)
+    }
+}
+
+impl std::hash::Hash for Board {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        state.write_u64(self.zobrist_hash());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::color::PuyoColor::*;
+
+    #[test]
+    fn same_board_same_hash() {
+        let board_1 = Board::from(concat!(
+            "RY....", // 2
+            "RBBG..", // 1
+        ));
+        let mut board_2 = Board::new();
+        for (x, c) in [
+            (1, RED),
+            (1, RED),
+            (2, BLUE),
+            (2, YELLOW),
+            (3, BLUE),
+            (4, GREEN),
+        ] {
+            board_2.place_puyo(x, c);
+        }
+
+        assert_eq!(board_1, board_2);
+        assert_eq!(board_1.zobrist_hash(), board_2.zobrist_hash());
+        assert_ne!(board_1.zobrist_hash(), Board::new().zobrist_hash());
+        assert_ne!(
+            Board::from("R.....").zobrist_hash(),
+            Board::from("G.....").zobrist_hash()
+        );
+        assert_ne!(
+            Board::from("R.....").zobrist_hash(),
+            Board::from(".R....").zobrist_hash()
+        );
+    }
+
+    #[test]
+    fn hash_set() {
+        let mut set = HashSet::new();
+        set.insert(Board::from("RRG..."));
+        set.insert(Board::from("RRG..."));
+        set.insert(Board::from("RRB..."));
+
+        assert_eq!(set.len(), 2);
+    }
+}
@@ -1,9 +1,14 @@
 #[derive(Clone, Default, PartialEq, Eq, Debug)]
-pub struct Chain(u32, u32, u32);
+pub struct Chain(u32, u32, u32, bool);
 
 impl Chain {
     pub const fn new(chain: u32, score: u32, frame: u32) -> Self {
-        Self(chain, score, frame)
+        Self(chain, score, frame, false)
+    }
+
+    /// Mark whether the board ended up empty (zenkeshi) after this chain.
+    pub const fn with_zenkeshi(self, zenkeshi: bool) -> Self {
+        Self(self.0, self.1, self.2, zenkeshi)
     }
 
     pub const fn chain(&self) -> u32 {
@@ -17,13 +22,22 @@ impl Chain {
     pub const fn frame(&self) -> u32 {
         self.2
     }
+
+    pub const fn zenkeshi(&self) -> bool {
+        self.3
+    }
 }
 
 impl std::ops::Add for Chain {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
-        Self(self.0 + rhs.0, self.1 + rhs.1, self.2 + rhs.2)
+        Self(
+            self.0 + rhs.0,
+            self.1 + rhs.1,
+            self.2 + rhs.2,
+            self.3 || rhs.3,
+        )
     }
 }
 
@@ -37,6 +51,7 @@ impl PartialOrd for Chain {
    1. asc score
    2. asc chain
    3. dsc frame
+   4. asc zenkeshi
 */
 impl Ord for Chain {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
@@ -44,6 +59,7 @@ impl Ord for Chain {
             .cmp(&other.1)
             .then_with(|| self.0.cmp(&other.0))
             .then_with(|| other.2.cmp(&self.2))
+            .then_with(|| self.3.cmp(&other.3))
     }
 }
 
@@ -72,4 +88,15 @@ mod tests {
 
         assert_eq!(v, expected);
     }
+
+    #[test]
+    fn zenkeshi() {
+        let chain = Chain::new(3, 4, 5);
+        let zenkeshi = Chain::new(3, 4, 5).with_zenkeshi(true);
+
+        assert!(!chain.zenkeshi());
+        assert!(zenkeshi.zenkeshi());
+        assert!(chain < zenkeshi);
+        assert!((Chain::default() + zenkeshi).zenkeshi());
+    }
 }
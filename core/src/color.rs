@@ -7,4 +7,6 @@ pub use self::{puyo_color::PuyoColor, real_color::RealColor};
 
 pub trait Color: Clone + Copy + PartialEq<Self> + Debug + Default + From<u8> + Into<u8> {
     fn is_normal_color(&self) -> bool;
+    /// Index in the normal colors, or None if not a normal color.
+    fn normal_index(&self) -> Option<usize>;
 }
@@ -5,7 +5,7 @@ use rand::Rng;
 use super::Color;
 
 /// [Color] impl for a simulation purpose.
-#[derive(Clone, Copy, PartialEq, Default)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum PuyoColor {
     #[default]
     EMPTY = 0,
@@ -29,6 +29,11 @@ impl Color for PuyoColor {
     fn is_normal_color(&self) -> bool {
         (*self as u8) & 0b100 != 0
     }
+
+    fn normal_index(&self) -> Option<usize> {
+        self.is_normal_color()
+            .then(|| (*self as u8 - PuyoColor::RED as u8) as usize)
+    }
 }
 
 impl PuyoColor {
@@ -1,7 +1,7 @@
 use super::Color;
 
 /// [Color] impl for an actual game.
-#[derive(Clone, Copy, PartialEq, Debug, Default)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
 pub enum RealColor {
     #[default]
     EMPTY = 0,
@@ -26,6 +26,11 @@ impl Color for RealColor {
     fn is_normal_color(&self) -> bool {
         ((*self as u8) + 1) & 0b1100 != 0
     }
+
+    fn normal_index(&self) -> Option<usize> {
+        self.is_normal_color()
+            .then(|| (*self as u8 - RealColor::RED as u8) as usize)
+    }
 }
 
 impl RealColor {
@@ -0,0 +1,451 @@
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    board::{ChainSteps, WIDTH},
+    ojama::random_ojama_cols,
+    placement::Placement,
+    player_state::PlayerState,
+    tumo::Tumos,
+};
+
+/// Maximum number of ojamas that can drop at once.
+pub const MAX_OJAMA_DROP: u32 = 30;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Player {
+    P1,
+    P2,
+}
+
+impl Player {
+    pub const fn opponent(&self) -> Self {
+        match self {
+            Player::P1 => Player::P2,
+            Player::P2 => Player::P1,
+        }
+    }
+
+    const fn index(&self) -> usize {
+        match self {
+            Player::P1 => 0,
+            Player::P2 => 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GameResult {
+    Win(Player),
+    Draw,
+}
+
+#[derive(Clone)]
+enum Phase {
+    /// Waiting for the next placement.
+    Control,
+    /// The placed tumo lands at `until`.
+    Place {
+        until: u32,
+    },
+    /// A chain is going on, and the next step pops at `until`.
+    Chain {
+        until: u32,
+        steps: ChainSteps,
+    },
+    /// Ojama is dropping, and the player can control again at `until`.
+    Ojama {
+        until: u32,
+    },
+    Dead,
+}
+
+/// Deterministic versus game, which advances both players frame by frame.
+///
+/// A waiting player stays idle until [Game::place] is called.
+#[derive(Clone)]
+pub struct Game {
+    frame: u32,
+    visible: usize,
+    states: [PlayerState; 2],
+    phases: [Phase; 2],
+    rng: StdRng,
+    result: Option<GameResult>,
+}
+
+impl Game {
+    /// Start a new game where both players share the same `tumos`.
+    /// `seed` decides the columns of the ojamas.
+    pub fn new(tumos: Tumos, visible: usize, seed: u64) -> Self {
+        Self::from_player_states(
+            PlayerState::initial_state(tumos.clone()),
+            PlayerState::initial_state(tumos),
+            visible,
+            seed,
+        )
+    }
+
+    pub fn from_player_states(
+        state_1p: PlayerState,
+        state_2p: PlayerState,
+        visible: usize,
+        seed: u64,
+    ) -> Self {
+        Self {
+            frame: 0,
+            visible,
+            states: [state_1p, state_2p],
+            phases: [Phase::Control, Phase::Control],
+            rng: StdRng::seed_from_u64(seed),
+            result: None,
+        }
+    }
+
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    pub fn result(&self) -> Option<GameResult> {
+        self.result
+    }
+
+    pub fn player_state(&self, player: Player) -> &PlayerState {
+        &self.states[player.index()]
+    }
+
+    /// Return (`player`'s state, opponent's state), with tumos limited to the visible ones.
+    pub fn visible_player_states(&self, player: Player) -> (PlayerState, PlayerState) {
+        PlayerState::limit_visible_tumos_pvp(
+            self.visible,
+            self.player_state(player),
+            self.player_state(player.opponent()),
+        )
+    }
+
+    /// true if `player` can (and should) place the next tumo.
+    pub fn is_waiting(&self, player: Player) -> bool {
+        self.result.is_none() && matches!(self.phases[player.index()], Phase::Control)
+    }
+
+    /// Place the next tumo of `player`, and return frames until it lands.
+    /// Return `None` if `player` is not waiting or `placement` is not placeable.
+    pub fn place(&mut self, player: Player, placement: &Placement) -> Option<u32> {
+        if !self.is_waiting(player) {
+            return None;
+        }
+
+        let state = &mut self.states[player.index()];
+        let tumo = state.tumos[0];
+        let (place_frame, bonus) = state.board.place_tumo(&tumo, placement)?;
+        state.carry_over += bonus;
+        state.tumos.rotate(self.visible);
+
+        self.phases[player.index()] = Phase::Place {
+            until: self.frame + place_frame,
+        };
+        Some(place_frame)
+    }
+
+    /// Advance the game by one frame.
+    pub fn step(&mut self) {
+        if self.result.is_some() {
+            return;
+        }
+
+        self.frame += 1;
+        for player in [Player::P1, Player::P2] {
+            self.states[player.index()].frame = self.frame;
+            self.advance(player);
+        }
+
+        self.result = match self.phases.each_ref().map(|p| matches!(p, Phase::Dead)) {
+            [true, true] => Some(GameResult::Draw),
+            [true, false] => Some(GameResult::Win(Player::P2)),
+            [false, true] => Some(GameResult::Win(Player::P1)),
+            [false, false] => None,
+        };
+    }
+
+    /// Advance the game until either player is waiting or the game is over.
+    pub fn step_until_waiting(&mut self) {
+        while self.result.is_none() && !self.is_waiting(Player::P1) && !self.is_waiting(Player::P2)
+        {
+            self.step();
+        }
+    }
+
+    /// Process every event of `player` which should happen by the current frame.
+    fn advance(&mut self, player: Player) {
+        let i = player.index();
+
+        loop {
+            let phase = std::mem::replace(&mut self.phases[i], Phase::Control);
+            self.phases[i] = match phase {
+                Phase::Place { until } | Phase::Ojama { until } | Phase::Chain { until, .. }
+                    if until > self.frame =>
+                {
+                    self.phases[i] = phase;
+                    return;
+                }
+                Phase::Control | Phase::Dead => {
+                    self.phases[i] = phase;
+                    return;
+                }
+                Phase::Place { .. } => Phase::Chain {
+                    until: self.frame,
+                    steps: self.states[i].board.chain_steps(1),
+                },
+                Phase::Chain { steps, .. } => self.pop(player, steps),
+                Phase::Ojama { .. } => {
+                    if self.states[i].board.is_dead() {
+                        Phase::Dead
+                    } else {
+                        Phase::Control
+                    }
+                }
+            };
+        }
+    }
+
+    /// Pop the next chain if any, or finish the chain and start dropping ojamas.
+    fn pop(&mut self, player: Player, mut steps: ChainSteps) -> Phase {
+        let state = &mut self.states[player.index()];
+
+        let Some(step) = steps.next() else {
+            let fired = steps.last_chain() > 0;
+            if fired && steps.is_zenkeshi() {
+                state.zenkeshi = true;
+            }
+            state.current_chain = 0;
+            if fired {
+                let opponent = &mut self.states[player.opponent().index()];
+                opponent.ojama_fixed += opponent.ojama_incoming;
+                opponent.ojama_incoming = 0;
+            }
+            return self.drop_ojama(player);
+        };
+
+        state.board = step.board;
+        state.score += step.score;
+        state.current_chain = step.chain + 1;
+
+        self.send_ojama(player, step.score);
+
+        Phase::Chain {
+            until: self.frame + step.frame,
+            steps,
+        }
+    }
+
+    /// Convert `chain_score` to ojamas, offset them and send the rest to the opponent.
+    fn send_ojama(&mut self, player: Player, chain_score: u32) {
+        let ojama = self.states[player.index()].send_ojama(chain_score);
+        self.states[player.opponent().index()].ojama_incoming += ojama;
+    }
+
+    fn drop_ojama(&mut self, player: Player) -> Phase {
+        let state = &mut self.states[player.index()];
+
+        let ojama = state.ojama_fixed.min(MAX_OJAMA_DROP);
+        state.ojama_fixed -= ojama;
+        let cols = random_ojama_cols(&mut self.rng, ojama as usize % WIDTH);
+        let ojama_frame = state.board.drop_ojama(ojama as usize, Some(cols));
+
+        Phase::Ojama {
+            until: self.frame + ojama_frame,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        board::{Board, BoardOps},
+        color::PuyoColor::{self, *},
+        ojama::ZENKESHI_BONUS,
+        tumo::Tumo,
+    };
+
+    /// Let `players` keep placing tumos vertically from left to right until `until` returns true.
+    fn play_until(game: &mut Game, players: &[Player], until: impl Fn(&Game) -> bool) {
+        let mut x = [0, 0];
+        while game.result().is_none() && !until(game) {
+            for player in players {
+                if game.is_waiting(*player) {
+                    let i = player.index();
+                    x[i] = x[i] % WIDTH + 1;
+                    game.place(*player, &Placement::new(x[i], 0)).unwrap();
+                }
+            }
+            game.step();
+        }
+    }
+
+    /// Tumos which never pop when placed by [play_until].
+    fn checkered_tumos() -> Tumos {
+        Tumos::new(&[Tumo::new(RED, GREEN), Tumo::new(GREEN, RED)].repeat(64))
+    }
+
+    fn ojama_count(state: &PlayerState) -> usize {
+        state.board.bits_with_color(PuyoColor::OJAMA).popcount()
+    }
+
+    #[test]
+    fn chain_sends_ojama() {
+        let board = Board::from(concat!(
+            "RY....", // 5
+            "RY....", // 4
+            "RBB...", // 3
+            "YBGG..", // 2
+            "RYBG..", // 1
+        ));
+        let tumos = Tumos::new(&[Tumo::new_zoro(GREEN)]);
+        let state_1p = PlayerState::new(board.clone(), tumos, 0, 0, 0, 0, 0, 0);
+        let state_2p = PlayerState::initial_state(checkered_tumos());
+        let mut game = Game::from_player_states(state_1p, state_2p, 3, 0);
+
+        let mut expected_board = board.clone();
+        let (_, bonus) = expected_board
+            .place_tumo(&Tumo::new_zoro(GREEN), &Placement::new(4, 0))
+            .unwrap();
+        let expected = expected_board.simulate();
+        assert_eq!(expected.chain(), 4);
+
+        game.place(Player::P1, &Placement::new(4, 0)).unwrap();
+        play_until(&mut game, &[Player::P2], |g| g.is_waiting(Player::P1));
+
+        let state_1p = game.player_state(Player::P1);
+        let state_2p = game.player_state(Player::P2);
+        let sent = (expected.score() + bonus) / 70;
+        assert_eq!(state_1p.score, expected.score());
+        assert_eq!(state_1p.carry_over, (expected.score() + bonus) % 70);
+        assert_eq!(state_1p.board, expected_board);
+        assert_eq!(state_2p.ojama_incoming, 0);
+        assert_eq!(
+            state_2p.ojama_fixed as usize + ojama_count(state_2p),
+            sent as usize
+        );
+
+        // ojama drops after the next placement of 2P
+        play_until(&mut game, &[Player::P2], |g| {
+            g.player_state(Player::P2).ojama_fixed == 0
+        });
+        assert_eq!(ojama_count(game.player_state(Player::P2)), sent as usize);
+    }
+
+    #[test]
+    fn zenkeshi_sets_flag() {
+        let board = Board::from(".BBB..");
+        let mut tumos = Tumos::new(&[Tumo::new_zoro(BLUE)]);
+        tumos.extend_randoms(1);
+        let state_1p = PlayerState::new(board, tumos, 0, 0, 0, 0, 0, 0);
+        let state_2p = PlayerState::initial_state(checkered_tumos());
+        let mut game = Game::from_player_states(state_1p, state_2p, 3, 0);
+
+        game.place(Player::P1, &Placement::new(5, 0)).unwrap();
+        play_until(&mut game, &[Player::P2], |g| g.is_waiting(Player::P1));
+
+        let state_1p = game.player_state(Player::P1);
+        assert!(state_1p.board.is_zenkeshi());
+        assert!(state_1p.zenkeshi);
+    }
+
+    #[test]
+    fn zenkeshi_bonus_on_next_fire() {
+        let board = Board::from(concat!(
+            ".RRR..", // 2
+            "RBBB.Y", // 1
+        ));
+        let mut tumos = Tumos::new(&[Tumo::new_zoro(BLUE)]);
+        tumos.extend_randoms(1);
+        let mut state_1p = PlayerState::new(board, tumos, 0, 0, 0, 0, 0, 0);
+        state_1p.zenkeshi = true;
+        let state_2p = PlayerState::initial_state(checkered_tumos());
+        let mut game = Game::from_player_states(state_1p, state_2p, 3, 0);
+
+        game.place(Player::P1, &Placement::new(5, 0)).unwrap();
+        play_until(&mut game, &[Player::P2], |g| g.is_waiting(Player::P1));
+
+        let state_1p = game.player_state(Player::P1);
+        let state_2p = game.player_state(Player::P2);
+        let sent = (100 + 320 + ZENKESHI_BONUS) / 70;
+        assert!(!state_1p.zenkeshi);
+        assert_eq!(state_1p.score, 100 + 320);
+        assert_eq!(
+            state_2p.ojama_fixed as usize + ojama_count(state_2p),
+            sent as usize
+        );
+    }
+
+    #[test]
+    fn chain_offsets_ojama() {
+        let board = Board::from(concat!(
+            ".RRR..", // 2
+            "RBBB..", // 1
+        ));
+        let mut tumos = Tumos::new(&[Tumo::new_zoro(BLUE)]);
+        tumos.extend_randoms(1);
+        let state = PlayerState::new(board, tumos, 0, 0, 0, 0, 0, 0);
+        let mut game = Game::from_player_states(state.clone(), state, 3, 0);
+
+        game.place(Player::P1, &Placement::new(5, 0)).unwrap();
+        game.place(Player::P2, &Placement::new(5, 0)).unwrap();
+        game.step_until_waiting();
+
+        for player in [Player::P1, Player::P2] {
+            let state = game.player_state(player);
+            assert_eq!(state.score, 100 + 320);
+            assert_eq!(state.ojama_fixed, 0);
+            assert_eq!(state.ojama_incoming, 0);
+            assert_eq!(ojama_count(state), 0);
+        }
+        assert_eq!(game.result(), None);
+    }
+
+    #[test]
+    fn drop_at_most_30_ojamas() {
+        let mut state_2p = PlayerState::initial_state(checkered_tumos());
+        state_2p.ojama_fixed = 40;
+        let state_1p = PlayerState::initial_state(checkered_tumos());
+        let mut game = Game::from_player_states(state_1p, state_2p, 3, 0);
+
+        play_until(&mut game, &[Player::P2], |g| {
+            g.player_state(Player::P2).ojama_fixed < 40
+        });
+
+        let state_2p = game.player_state(Player::P2);
+        assert_eq!(state_2p.ojama_fixed, 10);
+        assert_eq!(ojama_count(state_2p), 30);
+    }
+
+    #[test]
+    fn dead_player_loses() {
+        let board = Board::from([0, 0, 10, 0, 0, 0]);
+        let state_1p = PlayerState::initial_state(checkered_tumos());
+        let state_2p = PlayerState::new(board, checkered_tumos(), 0, 0, 0, 0, 0, 0);
+        let mut game = Game::from_player_states(state_1p, state_2p, 3, 0);
+
+        game.place(Player::P2, &Placement::new(3, 0)).unwrap();
+        play_until(&mut game, &[Player::P1], |_| false);
+
+        assert_eq!(game.result(), Some(GameResult::Win(Player::P1)));
+        assert!(!game.is_waiting(Player::P1));
+    }
+
+    #[test]
+    fn deterministic() {
+        let tumos = Tumos::new_random();
+        let play = || {
+            let mut game = Game::new(tumos.clone(), 3, 42);
+            game.states[1].ojama_fixed = 100;
+            play_until(&mut game, &[Player::P1, Player::P2], |g| g.frame() > 3000);
+            (
+                game.frame(),
+                game.result(),
+                game.player_state(Player::P2).board.clone(),
+            )
+        };
+
+        assert_eq!(play(), play());
+    }
+}
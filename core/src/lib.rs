@@ -4,6 +4,7 @@
 pub mod board;
 pub mod chain;
 pub mod color;
+pub mod game;
 pub mod ojama;
 pub mod placement;
 pub mod player_state;
@@ -1,10 +1,11 @@
 // ref. https://puyo-camp.jp/posts/71019
 
-use rand::seq::SliceRandom;
+use rand::{seq::SliceRandom, Rng};
 
 use crate::{
     board::{Board, WIDTH},
     color::PuyoColor,
+    player_state::PlayerState,
 };
 
 // 60 frames/sec
@@ -45,6 +46,9 @@ pub const fn ojama_frames_by_quantity(quan: usize) -> u32 {
     OJAMA_FRAMES_BY_QUANTITY[quan as usize]
 }
 
+/// Score added on the next fire after zenkeshi (30 ojamas with the normal rate).
+pub const ZENKESHI_BONUS: u32 = 70 * 30;
+
 pub const fn ojama_rate(frame: u32) -> u32 {
     if frame < 96 * FPS {
         70
@@ -73,6 +77,96 @@ pub const fn ojama_rate(frame: u32) -> u32 {
     }
 }
 
+/// Convert `score` into ojama, taking `carry_over` from previous chains into account.
+/// Return (ojama, new carry_over).
+pub const fn score_to_ojama(score: u32, frame: u32, carry_over: u32) -> (u32, u32) {
+    let rate = ojama_rate(frame);
+    let score = score + carry_over;
+    (score / rate, score % rate)
+}
+
+/// Offset (sousai) `ojama` against `ojama_fixed` first, then against `ojama_incoming`.
+/// Return (ojama left to send, new ojama_fixed, new ojama_incoming).
+pub const fn offset_ojama(ojama: u32, ojama_fixed: u32, ojama_incoming: u32) -> (u32, u32, u32) {
+    let offset_fixed = if ojama < ojama_fixed {
+        ojama
+    } else {
+        ojama_fixed
+    };
+    let ojama = ojama - offset_fixed;
+    let offset_incoming = if ojama < ojama_incoming {
+        ojama
+    } else {
+        ojama_incoming
+    };
+
+    (
+        ojama - offset_incoming,
+        ojama_fixed - offset_fixed,
+        ojama_incoming - offset_incoming,
+    )
+}
+
+impl PlayerState {
+    /// Convert `score` (with zenkeshi bonus if any) into ojama and offset it against the pending ojamas.
+    /// Return ojama to be sent to the opponent.
+    pub fn send_ojama(&mut self, score: u32) -> u32 {
+        let mut score = score;
+        if self.zenkeshi && score > 0 {
+            score += ZENKESHI_BONUS;
+            self.zenkeshi = false;
+        }
+
+        let (ojama, carry_over) = score_to_ojama(score, self.frame, self.carry_over);
+        let (ojama, ojama_fixed, ojama_incoming) =
+            offset_ojama(ojama, self.ojama_fixed, self.ojama_incoming);
+
+        self.carry_over = carry_over;
+        self.ojama_fixed = ojama_fixed;
+        self.ojama_incoming = ojama_incoming;
+
+        ojama
+    }
+
+    /// Total ojama which will drop on this player unless offset.
+    pub const fn ojama_pending(&self) -> u32 {
+        self.ojama_fixed + self.ojama_incoming
+    }
+
+    /// Drop the fixed ojama (up to 30) as after placing a tumo and the chain if any,
+    /// and return its frames. Offset the ojama with [PlayerState::send_ojama] before calling this.
+    /// The last few ojamas are dropped in [conservative_ojama_cols] since the real ones are random.
+    pub fn drop_fixed_ojama_conservatively(&mut self) -> u32 {
+        let ojama = self.ojama_fixed.min(30);
+        if ojama == 0 {
+            return 0;
+        }
+
+        let cols = conservative_ojama_cols(ojama as usize % WIDTH);
+        self.ojama_fixed -= ojama;
+        self.board.drop_ojama(ojama as usize, Some(cols))
+    }
+}
+
+/// Randomly choose `ones` columns to drop the last few ojamas, as a bitset of columns.
+pub fn random_ojama_cols<R: Rng>(rng: &mut R, ones: usize) -> u8 {
+    debug_assert!(ones < WIDTH);
+
+    (0..WIDTH)
+        .collect::<Vec<_>>()
+        .choose_multiple(rng, ones)
+        .fold(0, |m, &i| m | (1 << i))
+}
+
+/// Choose `ones` columns from the center, which is the worst case since the 3rd column is the death column.
+pub fn conservative_ojama_cols(ones: usize) -> u8 {
+    debug_assert!(ones < WIDTH);
+
+    [3, 4, 2, 5, 1, 6][..ones]
+        .iter()
+        .fold(0, |m, &x| m | (1 << (x - 1)))
+}
+
 impl Board {
     /// drop ojama, and return frames till the next controllable state
     pub fn drop_ojama(&mut self, ojama: usize, cols_bit: Option<u8>) -> u32 {
@@ -88,10 +182,7 @@ impl Board {
         let cols: u8 = if let Some(c) = cols_bit {
             c
         } else {
-            (0..WIDTH)
-                .collect::<Vec<_>>()
-                .choose_multiple(&mut rand::thread_rng(), ones)
-                .fold(0, |m, &i| m | (1 << i))
+            random_ojama_cols(&mut rand::thread_rng(), ones)
         };
         debug_assert!(cols.count_ones() as usize == ones);
 
@@ -120,12 +211,78 @@ impl Board {
 
         frames_by_position + frames_by_quantity
     }
+
+    /// Drop only the full rows of `ojama` over as many turns as needed (30 per turn).
+    /// The rest is ignored since it goes to random columns.
+    pub fn drop_ojama_rows(&mut self, ojama: usize) {
+        let mut rest = ojama / WIDTH * WIDTH;
+        while rest > 0 {
+            let ojama = rest.min(30);
+            self.drop_ojama(ojama, Some(0));
+            rest -= ojama;
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn score_to_ojama_with_carry_over() {
+        assert_eq!(score_to_ojama(0, 0, 0), (0, 0));
+        assert_eq!(score_to_ojama(4840, 0, 0), (69, 10));
+        assert_eq!(score_to_ojama(4840, 0, 60), (70, 0));
+        // margin time
+        assert_eq!(score_to_ojama(4840, 96 * FPS, 0), (93, 4));
+        assert_eq!(score_to_ojama(4840, 300 * FPS, 0), (4840, 0));
+    }
+
+    #[test]
+    fn offset_ojama_fixed_first() {
+        assert_eq!(offset_ojama(10, 0, 0), (10, 0, 0));
+        assert_eq!(offset_ojama(10, 4, 0), (6, 0, 0));
+        assert_eq!(offset_ojama(10, 4, 3), (3, 0, 0));
+        assert_eq!(offset_ojama(10, 12, 3), (0, 2, 3));
+        assert_eq!(offset_ojama(10, 6, 30), (0, 0, 26));
+    }
+
+    #[test]
+    fn send_ojama() {
+        let mut player_state = PlayerState {
+            carry_over: 50,
+            ojama_fixed: 5,
+            ojama_incoming: 3,
+            ..PlayerState::default()
+        };
+
+        // (1000 + 50) / 70 = 15
+        assert_eq!(player_state.send_ojama(1000), 7);
+        assert_eq!(player_state.carry_over, 0);
+        assert_eq!(player_state.ojama_pending(), 0);
+
+        player_state.ojama_incoming = 20;
+        assert_eq!(player_state.send_ojama(140), 0);
+        assert_eq!(player_state.ojama_fixed, 0);
+        assert_eq!(player_state.ojama_incoming, 18);
+    }
+
+    #[test]
+    fn send_ojama_with_zenkeshi() {
+        let mut player_state = PlayerState {
+            zenkeshi: true,
+            ..PlayerState::default()
+        };
+
+        assert_eq!(player_state.send_ojama(0), 0);
+        assert!(player_state.zenkeshi);
+
+        assert_eq!(player_state.send_ojama(140), 32);
+        assert!(!player_state.zenkeshi);
+
+        assert_eq!(player_state.send_ojama(140), 2);
+    }
+
     #[test]
     fn drop_ojama_1() {
         let mut board = Board::new();
@@ -145,6 +302,53 @@ mod tests {
         assert_eq!(frame, 64 + 25);
     }
 
+    #[test]
+    fn conservative_ojama_cols() {
+        assert_eq!(super::conservative_ojama_cols(0), 0);
+        assert_eq!(super::conservative_ojama_cols(1), 0b000100);
+        assert_eq!(super::conservative_ojama_cols(3), 0b001110);
+        assert_eq!(super::conservative_ojama_cols(5), 0b011111);
+    }
+
+    #[test]
+    fn drop_fixed_ojama_conservatively() {
+        let mut player_state = PlayerState {
+            board: Board::from(concat!(
+                "..GY..", // 2
+                "RBGYBR", // 1
+            )),
+            ojama_fixed: 9,
+            ..PlayerState::default()
+        };
+
+        // 1 row and 3 more in the center, burying the 3rd and 4th columns
+        let frame = player_state.drop_fixed_ojama_conservatively();
+
+        assert_eq!(
+            player_state.board,
+            Board::from(concat!(
+                "..oo..", // 4
+                ".ooo..", // 3
+                "ooGYoo", // 2
+                "RBGYBR", // 1
+            ))
+        );
+        assert_eq!(player_state.ojama_fixed, 0);
+        assert_eq!(frame, 60 + 1);
+    }
+
+    #[test]
+    fn drop_ojama_rows() {
+        let mut board = Board::from(concat!(
+            "..G...", // 2
+            "..B...", // 1
+        ));
+
+        board.drop_ojama_rows(41);
+
+        assert_eq!(board.height_array()[1..=WIDTH], [6, 6, 8, 6, 6, 6]);
+    }
+
     #[test]
     fn drop_ojama_2() {
         let mut board = Board::new();
@@ -6,7 +6,7 @@ pub mod placeable;
 mod placement;
 pub mod real_frame;
 
-#[derive(Clone, Copy, Default, Debug, PartialEq)]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
 pub struct Placement(usize, usize);
 
 impl Placement {
@@ -33,6 +33,19 @@ impl Placement {
         self.1
     }
 
+    /// Placement putting a tumo on the same cells with its axis and child swapped.
+    pub const fn swapped(&self) -> Self {
+        debug_assert!(self.is_valid());
+
+        match self.rot() {
+            0 => Self(self.0, 2),
+            1 => Self(self.0 + 1, 3),
+            2 => Self(self.0, 0),
+            3 => Self(self.0 - 1, 1),
+            _ => unreachable!(),
+        }
+    }
+
     pub const fn is_valid(&self) -> bool {
         if self.axis_x() < 1 || self.axis_x() > WIDTH || self.rot() > 3 {
             return false;
@@ -59,6 +72,14 @@ impl Placement {
 mod tests {
     use super::*;
 
+    #[test]
+    fn swapped() {
+        assert_eq!(Placement::new(3, 0).swapped(), Placement::new(3, 2));
+        assert_eq!(Placement::new(4, 1).swapped(), Placement::new(5, 3));
+        assert_eq!(Placement::new(1, 2).swapped(), Placement::new(1, 0));
+        assert_eq!(Placement::new(6, 3).swapped(), Placement::new(5, 1));
+    }
+
     #[test]
     fn child_x() {
         let placement = Placement::new(3, 0);
@@ -1,6 +1,6 @@
 use crate::{board::Board, tumo::Tumos};
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct PlayerState {
     pub board: Board,
     pub tumos: Tumos,
@@ -10,6 +10,8 @@ pub struct PlayerState {
     pub ojama_fixed: u32,
     pub ojama_incoming: u32,
     pub current_chain: u32,
+    /// Zenkeshi bonus will be added on the next fire.
+    pub zenkeshi: bool,
 }
 
 impl PlayerState {
@@ -39,6 +41,7 @@ impl PlayerState {
             ojama_fixed,
             ojama_incoming,
             current_chain,
+            zenkeshi: false,
         }
     }
 
@@ -98,4 +101,18 @@ mod tests {
         assert_eq!(player_state.tumos[2], tumos[2]);
         assert_eq!(player_state.board, Board::new());
     }
+
+    #[test]
+    fn hash_set() {
+        let tumos = Tumos::new(&[Tumo::new_zoro(RED), Tumo::new(BLUE, GREEN)]);
+        let player_state = PlayerState::initial_state(tumos);
+        let mut moved = player_state.clone();
+        moved.board.place_puyo(3, RED);
+
+        let set =
+            std::collections::HashSet::from([player_state.clone(), player_state.clone(), moved]);
+
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&player_state));
+    }
 }
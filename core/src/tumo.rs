@@ -1,7 +1,11 @@
+mod distribution;
+mod generator;
 mod tumo;
 mod tumos;
 
 pub use self::{
+    distribution::TumoDistribution,
+    generator::TumoGenerator,
     tumo::{RealTumo, Tumo},
     tumos::{RealTumos, Tumos},
 };
@@ -0,0 +1,174 @@
+use rand::Rng;
+
+use super::{Tumo, TUMO_LOOP};
+use crate::color::{Color, PuyoColor};
+
+/// Each color is dealt this many times in a loop (256 puyos of 4 colors).
+const PER_COLOR: u32 = (TUMO_LOOP * 2 / 4) as u32;
+
+/// Distribution of the pairs to be dealt, given the pairs already dealt in the current loop.
+///
+/// The game deals each of the 4 colors equally often in a loop of [TUMO_LOOP] pairs,
+/// so the rest of the loop is drawn from the remaining colors without replacement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TumoDistribution {
+    /// Number of pairs dealt in the current loop.
+    dealt: usize,
+    /// Remaining count of each of [PuyoColor::normal_colors] in the current loop.
+    remaining: [u32; 4],
+}
+
+impl Default for TumoDistribution {
+    /// Distribution at the start of a loop.
+    fn default() -> Self {
+        Self {
+            dealt: 0,
+            remaining: [PER_COLOR; 4],
+        }
+    }
+}
+
+impl TumoDistribution {
+    /// Remaining count of each of [PuyoColor::normal_colors] for the next pair.
+    pub fn remaining(&self) -> [u32; 4] {
+        // a new loop starts, or the dealt pairs were not balanced
+        if self.dealt == TUMO_LOOP || self.remaining.iter().sum::<u32>() < 2 {
+            return [PER_COLOR; 4];
+        }
+        self.remaining
+    }
+
+    /// Mark `tumo` as dealt.
+    pub fn observe(&mut self, tumo: &Tumo) {
+        if self.dealt == TUMO_LOOP {
+            *self = Self::default();
+        }
+
+        self.dealt += 1;
+        for color in [tumo.axis(), tumo.child()] {
+            if let Some(i) = color.normal_index() {
+                self.remaining[i] = self.remaining[i].saturating_sub(1);
+            }
+        }
+    }
+
+    /// Pairs distinct regardless of the order which can be dealt next, each with the number of
+    /// ordered draws of it. The probability of a pair is its count divided by the sum of them.
+    pub fn next_pairs(&self) -> Vec<(Tumo, u32)> {
+        let colors = PuyoColor::normal_colors();
+        let remaining = self.remaining();
+
+        let mut pairs = Vec::with_capacity(10);
+        for i in 0..colors.len() {
+            for j in i..colors.len() {
+                let count = if i == j {
+                    remaining[i] * remaining[i].saturating_sub(1)
+                } else {
+                    2 * remaining[i] * remaining[j]
+                };
+                if count > 0 {
+                    pairs.push((Tumo::new(colors[i], colors[j]), count));
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Exact probability that `tumo` (in either order) is dealt next.
+    pub fn probability(&self, tumo: &Tumo) -> f64 {
+        let pairs = self.next_pairs();
+        let total: u32 = pairs.iter().map(|(_, count)| count).sum();
+        let swapped = Tumo::new(tumo.child(), tumo.axis());
+
+        pairs
+            .iter()
+            .find(|(pair, _)| pair == tumo || *pair == swapped)
+            .map_or(0.0, |(_, count)| *count as f64 / total as f64)
+    }
+
+    /// Draw the next pair and mark it as dealt.
+    pub fn sample<R: Rng>(&mut self, rng: &mut R) -> Tumo {
+        let mut remaining = self.remaining();
+        let colors = PuyoColor::normal_colors();
+
+        let axis = draw(&mut remaining, rng);
+        let child = draw(&mut remaining, rng);
+        let tumo = Tumo::new(colors[axis], colors[child]);
+
+        self.observe(&tumo);
+        tumo
+    }
+}
+
+/// Draw an index with the probability proportional to `counts`, and take it out.
+fn draw<R: Rng>(counts: &mut [u32; 4], rng: &mut R) -> usize {
+    let mut r = rng.gen_range(0..counts.iter().sum::<u32>());
+    for (i, count) in counts.iter_mut().enumerate() {
+        if r < *count {
+            *count -= 1;
+            return i;
+        }
+        r -= *count;
+    }
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::color::PuyoColor::*;
+
+    #[test]
+    fn uniform_at_start() {
+        let distribution = TumoDistribution::default();
+        let pairs = distribution.next_pairs();
+
+        assert_eq!(pairs.len(), 10);
+        assert_eq!(pairs.iter().map(|(_, count)| count).sum::<u32>(), 256 * 255);
+
+        let zoro = distribution.probability(&Tumo::new_zoro(RED));
+        let non_zoro = distribution.probability(&Tumo::new(BLUE, RED));
+        assert!((zoro - 63.0 / 1020.0).abs() < 1e-9);
+        assert!((non_zoro - 128.0 / 1020.0).abs() < 1e-9);
+        assert_eq!(non_zoro, distribution.probability(&Tumo::new(RED, BLUE)));
+    }
+
+    #[test]
+    fn conditioned_on_dealt_pairs() {
+        let mut distribution = TumoDistribution::default();
+        for _ in 0..32 {
+            distribution.observe(&Tumo::new_zoro(RED));
+        }
+
+        // every red has been dealt
+        assert_eq!(distribution.remaining(), [0, 64, 64, 64]);
+        assert_eq!(distribution.next_pairs().len(), 6);
+        assert_eq!(distribution.probability(&Tumo::new(RED, GREEN)), 0.0);
+        for _ in 0..100 {
+            let tumo = distribution.clone().sample(&mut thread_rng());
+            assert!(tumo.axis() != RED && tumo.child() != RED);
+        }
+    }
+
+    #[test]
+    fn sample_balanced_loop() {
+        let mut distribution = TumoDistribution::default();
+        let mut counts = [0; 4];
+        for _ in 0..TUMO_LOOP {
+            let tumo = distribution.sample(&mut thread_rng());
+            counts[tumo.axis().normal_index().unwrap()] += 1;
+            counts[tumo.child().normal_index().unwrap()] += 1;
+        }
+        assert_eq!(counts, [PER_COLOR; 4]);
+
+        // the next loop starts over
+        assert_eq!(distribution.remaining(), [PER_COLOR; 4]);
+        distribution.sample(&mut thread_rng());
+        assert_eq!(
+            distribution.remaining().iter().sum::<u32>(),
+            4 * PER_COLOR - 2
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use super::{tumo::Pair, RealTumos, Tumos, TUMO_LOOP};
+use crate::color::{Color, PuyoColor, RealColor};
+
+/// Number of puyos in a loop.
+const PUYOS: usize = TUMO_LOOP * 2;
+
+/// Generator of tumo sequences following the algorithm of Puyo Puyo Tsu, so that a sequence
+/// is reproduced exactly from its seed.
+///
+/// The puyos of each of the 3, 4 and 5 color sequences are dealt equally from the selected
+/// colors and then shuffled, and the first 2 pairs are shared with the 3 color sequence.
+pub struct TumoGenerator {
+    state: u32,
+}
+
+impl TumoGenerator {
+    pub fn new(seed: u32) -> Self {
+        Self { state: seed }
+    }
+
+    /// Linear congruential generator of the game.
+    fn next(&mut self) -> u32 {
+        self.state = self
+            .state
+            .wrapping_mul(0x5D58_8B65)
+            .wrapping_add(0x0026_9EC3);
+        self.state
+    }
+
+    /// One loop of pairs with `num_colors` (3 to 5) colors.
+    pub fn generate(mut self, num_colors: usize) -> RealTumos {
+        assert!(
+            (3..=5).contains(&num_colors),
+            "number of colors should be 3 to 5: {}",
+            num_colors
+        );
+
+        let colors = self.select_colors();
+        let mut sequences: [[RealColor; PUYOS]; 3] =
+            std::array::from_fn(|i| std::array::from_fn(|n| colors[n % (i + 3)]));
+
+        for sequence in &mut sequences {
+            self.shuffle(sequence, 15, 16);
+            self.shuffle(sequence, 35, 32);
+            self.shuffle(sequence, 3, 64);
+        }
+
+        // the first 2 pairs have at most 3 colors
+        let (first, rest) = sequences.split_first_mut().unwrap();
+        for sequence in rest {
+            sequence[..4].copy_from_slice(&first[..4]);
+        }
+
+        let sequence = &sequences[num_colors - 3];
+        RealTumos::new(
+            &sequence
+                .chunks(2)
+                .map(|pair| Pair::new(pair[0], pair[1]))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Order of [RealColor::normal_colors] to be used, of which the first ones are selected.
+    fn select_colors(&mut self) -> [RealColor; 5] {
+        let mut colors = *RealColor::normal_colors();
+        for i in (1..colors.len()).rev() {
+            let j = (self.next() >> 16) as usize % (i + 1);
+            colors.swap(i, j);
+        }
+        colors
+    }
+
+    /// Swap a random puyo of each block of `block` puyos with one of the next block, `rounds` times.
+    fn shuffle(&mut self, sequence: &mut [RealColor; PUYOS], rounds: usize, block: usize) {
+        let shift = 32 - block.trailing_zeros();
+        for _ in 0..rounds {
+            for i in 0..PUYOS / block {
+                let a = ((self.next() >> shift) as usize + i * block) % PUYOS;
+                let b = ((self.next() >> shift) as usize + (i + 1) * block) % PUYOS;
+                sequence.swap(a, b);
+            }
+        }
+    }
+}
+
+impl Tumos {
+    /// One loop of the 4 color sequence of `seed`, with the colors renamed in order of
+    /// [RealColor::normal_colors].
+    pub fn from_seed(seed: u32) -> Self {
+        let real_tumos = TumoGenerator::new(seed).generate(4);
+
+        let mut used = [false; 5];
+        for i in 0..real_tumos.len() {
+            for color in [real_tumos[i].axis(), real_tumos[i].child()] {
+                used[color.normal_index().unwrap()] = true;
+            }
+        }
+        let mut colors = PuyoColor::normal_colors().iter();
+        let rename: Vec<Option<PuyoColor>> = used
+            .iter()
+            .map(|&used| used.then(|| *colors.next().unwrap()))
+            .collect();
+        let rename = |color: RealColor| rename[color.normal_index().unwrap()].unwrap();
+
+        Tumos::new(
+            &(0..real_tumos.len())
+                .map(|i| Pair::new(rename(real_tumos[i].axis()), rename(real_tumos[i].child())))
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn colors(tumos: &RealTumos, range: std::ops::Range<usize>) -> HashSet<u8> {
+        range
+            .flat_map(|i| [tumos[i].axis(), tumos[i].child()])
+            .map(|color| color.into())
+            .collect()
+    }
+
+    #[test]
+    fn reproducible_by_seed() {
+        assert!(Tumos::from_seed(42) == Tumos::from_seed(42));
+        assert!(Tumos::from_seed(42) != Tumos::from_seed(43));
+
+        let tumos = Tumos::from_seed(42);
+        assert_eq!(tumos.len(), TUMO_LOOP);
+        // each color is dealt equally often except for the first 2 pairs
+        let mut counts = [0usize; 4];
+        for i in 0..tumos.len() {
+            for color in [tumos[i].axis(), tumos[i].child()] {
+                counts[color.normal_index().unwrap()] += 1;
+            }
+        }
+        assert!(counts
+            .iter()
+            .all(|count| count.abs_diff(TUMO_LOOP / 2) <= 4));
+    }
+
+    #[test]
+    fn colors_of_sequences() {
+        for seed in 0..100 {
+            for num_colors in 3..=5 {
+                let tumos = TumoGenerator::new(seed).generate(num_colors);
+                assert_eq!(tumos.len(), TUMO_LOOP);
+                assert_eq!(colors(&tumos, 0..TUMO_LOOP).len(), num_colors);
+                assert!(colors(&tumos, 0..2).len() <= 3);
+            }
+
+            // the first 2 pairs are the same regardless of the number of colors
+            let first = |num_colors| {
+                let tumos = TumoGenerator::new(seed).generate(num_colors);
+                [tumos[0], tumos[1]]
+            };
+            assert_eq!(first(3), first(4));
+            assert_eq!(first(3), first(5));
+        }
+    }
+}
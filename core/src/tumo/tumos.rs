@@ -1,17 +1,24 @@
-use std::{array::from_fn, ops::Index};
+use std::{
+    array::from_fn,
+    hash::{Hash, Hasher},
+    ops::Index,
+};
 
-use super::{tumo::Pair, TUMO_LOOP};
+use super::{tumo::Pair, TumoDistribution, TUMO_LOOP};
 use crate::color::{Color, PuyoColor, RealColor};
 
 pub type Tumos = PairQueue<PuyoColor>;
 pub type RealTumos = PairQueue<RealColor>;
 
+/// Equality and hash only consider the pairs from the head, i.e. `self[0..available_tumo_len()]`.
 #[derive(Clone)]
 pub struct PairQueue<C: Color> {
     len: usize,
     head: usize,
     pairs: [Pair<C>; TUMO_LOOP],
     looped: bool,
+    /// Number of each normal color dealt in the current loop before the first pair.
+    dealt_before: [u16; 5],
 }
 
 impl<C: Color> Default for PairQueue<C> {
@@ -21,6 +28,7 @@ impl<C: Color> Default for PairQueue<C> {
             head: 0,
             pairs: from_fn(|_| Default::default()),
             looped: false,
+            dealt_before: [0; 5],
         }
     }
 }
@@ -92,7 +100,21 @@ impl<C: Color> PairQueue<C> {
         }
 
         let head_idx = head_idx.unwrap_or(self.head);
-        let mut tumos = Self::default();
+        let mut tumos = Self {
+            dealt_before: self.dealt_before,
+            ..Self::default()
+        };
+        // pairs before the head are hidden, but still affect the colors to be dealt
+        for i in 0..head_idx {
+            if let Some(pair) = self.get_raw(i) {
+                for i in [pair.axis(), pair.child()]
+                    .iter()
+                    .filter_map(Color::normal_index)
+                {
+                    tumos.dealt_before[i] += 1;
+                }
+            }
+        }
         for i in head_idx..(head_idx + visible) {
             if let Some(tumo) = self.get_raw(i) {
                 tumos.push(&tumo);
@@ -131,17 +153,70 @@ impl<C: Color> PairQueue<C> {
 }
 
 impl PairQueue<PuyoColor> {
+    /// A loop of random pairs with each color dealt equally often.
     pub fn new_random() -> Self {
         let mut tumos = Self::default();
-        for _ in 0..TUMO_LOOP {
-            tumos.push(&Pair::<PuyoColor>::new_random());
-        }
+        tumos.extend_randoms(TUMO_LOOP);
         tumos
     }
 
+    /// Push random pairs drawn from [PairQueue::distribution].
     pub fn extend_randoms(&mut self, len: usize) {
+        let mut distribution = self.distribution();
+        let mut rng = rand::thread_rng();
         for _ in 0..len {
-            self.push(&Pair::<PuyoColor>::new_random());
+            self.push(&distribution.sample(&mut rng));
+        }
+    }
+
+    /// Distribution of the pairs following the last pair,
+    /// given every pair dealt so far in the current loop (including the hidden ones).
+    pub fn distribution(&self) -> TumoDistribution {
+        let mut distribution = TumoDistribution::default();
+
+        // only the number of each color matters for the pairs before the first one
+        let mut dealt_before = self.dealt_before;
+        for (i, color) in PuyoColor::normal_colors().iter().enumerate() {
+            while dealt_before[i] >= 2 {
+                distribution.observe(&Pair::new_zoro(*color));
+                dealt_before[i] -= 2;
+            }
+        }
+        let mut rest = PuyoColor::normal_colors()
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| dealt_before[*i] == 1)
+            .map(|(_, color)| color);
+        while let (Some(&axis), Some(&child)) = (rest.next(), rest.next()) {
+            distribution.observe(&Pair::new(axis, child));
+        }
+
+        for i in 0..self.len {
+            distribution.observe(&self.pairs[i]);
+        }
+        distribution
+    }
+}
+
+impl<C: Color> PairQueue<C> {
+    fn available_tumos(&self) -> impl Iterator<Item = &Pair<C>> {
+        (0..self.available_tumo_len()).map(|i| &self[i])
+    }
+}
+
+impl<C: Color> PartialEq for PairQueue<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.available_tumos().eq(other.available_tumos())
+    }
+}
+
+impl<C: Color> Eq for PairQueue<C> {}
+
+impl<C: Color + Hash> Hash for PairQueue<C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.available_tumo_len());
+        for pair in self.available_tumos() {
+            pair.hash(state);
         }
     }
 }
@@ -274,6 +349,26 @@ mod tests {
         assert_eq!(tumos[1].axis(), YELLOW);
     }
 
+    #[test]
+    fn eq_from_head() {
+        let mut tumos = Tumos::from("rrbygr");
+        tumos.rotate(0);
+        let mut hidden = tumos.clone();
+        hidden.set_raw(0, Tumo::new(BLUE, BLUE));
+
+        assert!(tumos == Tumos::from("bygr"));
+        assert!(tumos == hidden);
+        assert!(tumos != Tumos::from("bygg"));
+        assert!(tumos != Tumos::from("by"));
+
+        let hash = |tumos: &Tumos| {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            tumos.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&tumos), hash(&Tumos::from("bygr")));
+    }
+
     #[test]
     #[should_panic]
     fn push_max_out() {
@@ -296,6 +391,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn new_random_is_balanced() {
+        let tumos = Tumos::new_random();
+        let mut counts = [0; 4];
+        for i in 0..tumos.len() {
+            for color in [tumos[i].axis(), tumos[i].child()] {
+                counts[color.normal_index().unwrap()] += 1;
+            }
+        }
+        assert_eq!(counts, [TUMO_LOOP / 2; 4]);
+    }
+
+    #[test]
+    fn distribution_counts_hidden_pairs() {
+        let mut tumos = Tumos::new_random();
+        tumos.len = 10;
+        tumos.head = 7;
+
+        let sliced = tumos.slice_visible_tumos(3, None);
+        assert_eq!(sliced.distribution(), tumos.distribution());
+
+        let mut extended = sliced.clone();
+        extended.extend_randoms(5);
+        assert_eq!(extended.available_tumo_len(), 8);
+    }
+
     #[test]
     fn slice_visible_tumos() {
         let mut tumos = Tumos::new_random();
@@ -1,5 +1,7 @@
-use bot::Nova;
-use nova_simulator::simulate_1p;
+use core::tumo::Tumos;
+
+use bot::{Nova, NovaConfig};
+use nova_simulator::{seed_from_args, simulate_1p};
 
 const SIMULATE_N: usize = 1000;
 
@@ -10,9 +12,14 @@ const SIMULATE_N: usize = 1000;
 fn main() {
     let mut score_cnt = [0; 20];
     let mut think_ms_avg = 0.0;
+    let nova = Nova::new(NovaConfig::from_args());
+    // the i-th game is played with the tumos of `seed + i`
+    let seed = seed_from_args();
+    println!("seed: {}", seed);
 
     for sim in 1..=SIMULATE_N {
-        let simulate_result = simulate_1p(Nova::default(), None, Some(2));
+        let tumos = Tumos::from_seed(seed.wrapping_add(sim as u32));
+        let simulate_result = simulate_1p(&nova, Some(tumos), Some(2));
         think_ms_avg += simulate_result
             .decisions
             .iter()
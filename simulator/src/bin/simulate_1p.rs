@@ -1,9 +1,12 @@
-use bot::Nova;
-use nova_simulator::simulate_1p;
+use core::tumo::Tumos;
+
+use bot::{Nova, NovaConfig};
+use nova_simulator::{seed_from_args, simulate_1p};
 
 fn main() {
-    let nova = Nova::default();
-    let simulate_result = simulate_1p(nova, None, Some(2));
+    let nova = Nova::new(NovaConfig::from_args());
+    let seed = seed_from_args();
+    let simulate_result = simulate_1p(&nova, Some(Tumos::from_seed(seed)), Some(2));
 
     let think_ms_avg = simulate_result
         .decisions
@@ -13,6 +16,7 @@ fn main() {
         / simulate_result.decisions.len() as f64;
 
     println!("simulate result:");
+    println!(">  seed: {}", seed);
     println!("> score: {}", simulate_result.score);
     println!("> think: {}", think_ms_avg);
     println!(">   url: {}", simulate_result.create_puyop_url());
@@ -1,5 +1,7 @@
 mod simulate_1p;
+mod simulate_2p;
 mod simulate_result;
 
-pub use simulate_1p::simulate_1p;
+pub use simulate_1p::{seed_from_args, simulate_1p};
+pub use simulate_2p::simulate_2p;
 pub use simulate_result::simulate_1p_result::Simulate1PResult;
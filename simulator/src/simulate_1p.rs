@@ -4,7 +4,20 @@ use bot::Nova;
 
 use crate::simulate_result::simulate_1p_result::Simulate1PResult;
 
-pub fn simulate_1p(nova: Nova, tumos: Option<Tumos>, think_frame: Option<u32>) -> Simulate1PResult {
+/// Seed of the tumos given by `--seed`, or a random one if not given.
+/// The same tumos are replayed with [Tumos::from_seed].
+pub fn seed_from_args() -> u32 {
+    std::env::args()
+        .skip_while(|arg| arg != "--seed")
+        .nth(1)
+        .map_or_else(rand::random, |seed| seed.parse().expect("invalid seed"))
+}
+
+pub fn simulate_1p(
+    nova: &Nova,
+    tumos: Option<Tumos>,
+    think_frame: Option<u32>,
+) -> Simulate1PResult {
     // TODO: pass visible as parameter
     let visible = 3;
 
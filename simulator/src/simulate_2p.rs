@@ -0,0 +1,41 @@
+use core::{
+    game::{Game, GameResult, Player},
+    ojama::FPS,
+};
+
+use bot::Nova;
+
+/// The game is a draw if it does not end by then.
+pub const MAX_FRAME: u32 = 300 * FPS;
+
+/// Play `game` between `nova_1p` and `nova_2p` until it ends, and return the result.
+/// A player whose decision is not placeable loses.
+pub fn simulate_2p(
+    nova_1p: &Nova,
+    nova_2p: &Nova,
+    mut game: Game,
+    think_frame: Option<u32>,
+) -> GameResult {
+    while game.result().is_none() && game.frame() < MAX_FRAME {
+        for (player, nova) in [(Player::P1, nova_1p), (Player::P2, nova_2p)] {
+            if !game.is_waiting(player) {
+                continue;
+            }
+
+            let (player_state, opponent) = game.visible_player_states(player);
+            let decision = nova.think(&player_state, Some(&opponent), think_frame);
+            let placed = decision
+                .placements
+                .first()
+                .and_then(|placement| game.place(player, placement));
+            if placed.is_none() {
+                println!("unplaceable...");
+                return GameResult::Win(player.opponent());
+            }
+        }
+
+        game.step_until_waiting();
+    }
+
+    game.result().unwrap_or(GameResult::Draw)
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use bot::{
+    searcher::{ChokudaiSearcher, MonteCarloBeamSearcher, Searcher},
+    tumo_pattern, Nova, NovaConfig, Opening, OpeningBook,
+};
+use nova_tuner::book::{canonical_patterns, rollout_score, search_line};
+
+/// Number of tumos placed after the opening in each rollout.
+const ROLLOUT_TUMOS: usize = 30;
+
+fn arg(name: &str) -> Option<String> {
+    std::env::args().skip_while(|arg| arg != name).nth(1)
+}
+
+fn arg_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    arg(name).map_or(default, |value| {
+        value
+            .parse()
+            .unwrap_or_else(|_| panic!("invalid value for `{}`: {}", name, value))
+    })
+}
+
+fn main() {
+    let config = NovaConfig::from_args();
+    // Length of opening lines
+    let len = arg_or("--tumos", 2);
+    // Search time for each tumo of the opening
+    let millis = arg_or("--millis", 10000);
+    let samples = arg_or("--samples", 100);
+    let rollouts = arg_or("--rollouts", 10);
+    // Generated book is saved here in the same format as `--openings`
+    let output = arg("--output").unwrap_or_else(|| "openings.toml".to_owned());
+
+    let pool = Arc::new(config.worker_pool());
+    let searchers: Vec<(&str, Box<dyn Searcher>)> = vec![
+        (
+            "monte_carlo_beam",
+            Box::new(MonteCarloBeamSearcher::new(pool.clone()).with_samples(samples)),
+        ),
+        ("chokudai", Box::new(ChokudaiSearcher)),
+    ];
+    // rollouts should not follow the book being generated
+    let nova = Nova::with_pool(
+        NovaConfig {
+            opening_book: OpeningBook { openings: vec![] },
+            ..config.clone()
+        },
+        pool,
+    );
+
+    let patterns = canonical_patterns(len);
+    let mut book = OpeningBook { openings: vec![] };
+    for (i, tumos) in patterns.iter().enumerate() {
+        let pattern = tumo_pattern(tumos);
+        println!("> pattern {} ({}/{})", pattern, i + 1, patterns.len());
+
+        let (score, placements) = searchers
+            .iter()
+            .map(|(name, searcher)| {
+                let (board, placements) =
+                    search_line(searcher.as_ref(), &config.evaluators.build, tumos, millis);
+                let score = rollout_score(&nova, &board, rollouts, ROLLOUT_TUMOS);
+                println!("- {:>16}: {:>6}", name, score);
+                (score, placements)
+            })
+            .max_by_key(|(score, _)| *score)
+            .unwrap();
+
+        book.openings.push(Opening::new(
+            format!("generated {} ({})", pattern, score),
+            tumos,
+            &placements,
+        ));
+        book.save(&output);
+        println!("> saved to {}", output);
+        println!();
+    }
+}
@@ -1,7 +1,12 @@
-use bot::evaluator::*;
+use core::player_state::PlayerState;
+
+use bot::{evaluator::*, NovaConfig};
 use itertools::izip;
-use nova_tuner::simulate::select_best_evaluator_overrider;
+use nova_tuner::simulate::{
+    select_best_evaluators, select_best_evaluators_2p, GAMES_PER_THREAD, GAMES_PER_THREAD_2P,
+};
 use rand::Rng;
+use simulator::seed_from_args;
 
 macro_rules! features {
     [$([$sign:tt, $delta_max:literal, $feat:ident]),* $(,)?] => {
@@ -24,70 +29,136 @@ macro_rules! features {
 }
 
 fn main() {
-    // Evaluator to tune
-    let mut eval = BUILD_ENDGAME;
+    let config = NovaConfig::from_args();
+    let mut evaluators = config.evaluators;
+    // With `--zenkeshi`, the zenkeshi evaluator is tuned by 2p games where both players
+    // start with an all-clear, since it's never selected in 1p games
+    let zenkeshi = std::env::args().any(|arg| arg == "--zenkeshi");
+    // Evaluators to tune: the anchors are tuned together if tapered
+    let anchors: Vec<fn(&mut EvaluatorSet) -> &mut Evaluator> = if zenkeshi {
+        vec![|e| &mut e.zenkeshi]
+    } else if evaluators.taper {
+        vec![|e| &mut e.build, |e| &mut e.build_midgame, |e| {
+            &mut e.build_endgame
+        }]
+    } else {
+        vec![|e| &mut e.build_endgame]
+    };
+    // Tuned weights are saved here in the same format as `--weights`
+    let output = std::env::args()
+        .skip_while(|arg| arg != "--output")
+        .nth(1)
+        .unwrap_or_else(|| "weights.toml".to_owned());
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    // Tumos of the games are taken from the seeds following this one (or `--seed`)
+    let mut seed = seed_from_args();
+    println!("> seed: {}", seed);
 
     let targets: Vec<(
         &str,
         i32,
         fn(Evaluator, i32) -> Evaluator,
         fn(&Evaluator) -> i32,
-    )> = features![
-        // [-, 10, bump],
-        // [-, 15, dent],
-        // [-, 10, dead_cells],
-        // [+, 15, conn_2_v],
-        // [+, 15, conn_2_h],
-        // [-, 10, non_u_shape],
-        [-, 10, non_u_shape_sq],
-        // [-, 10, frame],
-        // [-, 10, frame_by_chain],
-        // [-, 10, frame_by_chigiri],
-        // [-, 10, detected_need],
-        // [+, 15, detected_chain],
-        // [+, 10, detected_score_per_k],
+    )> = if zenkeshi {
+        features![
+            [-, 10, bump],
+            [-, 15, dent],
+            [-, 3, frame],
+            [-, 3, frame_by_chain],
+            [-, 15, detected_need],
+            [+, 15, detected_chain],
+            [+, 10, detected_score_per_k],
+        ]
+    } else {
+        features![
+            // [-, 10, bump],
+            // [-, 15, dent],
+            // [-, 10, dead_cells],
+            // [+, 15, conn_2_v],
+            // [+, 15, conn_2_h],
+            // [-, 10, non_u_shape],
+            [-, 10, non_u_shape_sq],
+            // [-, 10, frame],
+            // [-, 10, frame_by_chain],
+            // [-, 10, frame_by_chigiri],
+            // [-, 10, detected_need],
+            // [+, 15, detected_chain],
+            // [+, 10, detected_score_per_k],
 
-        // no-op since max_depth in detect_potential_chain is 1
-        // [-, 10, detected_keys],
-    ];
+            // no-op since max_depth in detect_potential_chain is 1
+            // [-, 10, detected_keys],
+        ]
+    };
+    let initial_state = PlayerState {
+        zenkeshi: true,
+        ..PlayerState::default()
+    };
 
-    let initial_values: Vec<(&str, i32)> = targets
+    // (anchor name, feature name) of each tuned value
+    let names: Vec<(&str, &str)> = anchors
         .iter()
-        .map(|target| (target.0, target.3(&eval)))
+        .flat_map(|anchor| {
+            let name = anchor(&mut evaluators).name;
+            targets.iter().map(move |target| (name, target.0))
+        })
         .collect();
-    let max_feat_len = initial_values.iter().map(|t| t.0.len()).max().unwrap();
+    let values = |evaluators: &mut EvaluatorSet| -> Vec<i32> {
+        anchors
+            .iter()
+            .flat_map(|anchor| {
+                let eval = *anchor(evaluators);
+                targets.iter().map(move |target| target.3(&eval))
+            })
+            .collect()
+    };
+    let initial_values = values(&mut evaluators);
+    let max_name_len = names
+        .iter()
+        .map(|(a, f)| a.len() + f.len() + 3)
+        .max()
+        .unwrap();
 
     for i in 1..=100 {
         println!("> SPSA iteration {}", i);
-        let before_values: Vec<i32> = targets.iter().map(|target| target.3(&eval)).collect();
+        let before_values = values(&mut evaluators);
 
         for (_, delta_max, tweaker, _) in &targets {
-            let delta = rand::thread_rng().gen_range(2..=*delta_max);
-
-            let w_org = eval.clone();
-            let w_pos = tweaker(eval, delta);
-            let w_neg = tweaker(eval, -delta);
+            // perturb all the anchors simultaneously
+            let mut pos = evaluators;
+            let mut neg = evaluators;
+            for anchor in &anchors {
+                let sign = if rand::thread_rng().gen() { 1 } else { -1 };
+                let delta = sign * rand::thread_rng().gen_range(2..=*delta_max);
+                *anchor(&mut pos) = tweaker(*anchor(&mut pos), delta);
+                *anchor(&mut neg) = tweaker(*anchor(&mut neg), -delta);
+            }
 
-            let o_org: EvaluatorOverrider = (eval.name, w_org);
-            let o_pos: EvaluatorOverrider = (eval.name, w_pos);
-            let o_neg: EvaluatorOverrider = (eval.name, w_neg);
-
-            let best_o = select_best_evaluator_overrider(vec![o_neg, o_pos, o_org], 85000);
-            eval = best_o.1;
+            let candidates = vec![neg, pos, evaluators];
+            if zenkeshi {
+                evaluators =
+                    select_best_evaluators_2p(&config, candidates, &initial_state, threads, seed);
+                seed = seed.wrapping_add((threads * GAMES_PER_THREAD_2P) as u32);
+            } else {
+                evaluators = select_best_evaluators(&config, candidates, 85000, threads, seed);
+                seed = seed.wrapping_add((threads * GAMES_PER_THREAD) as u32);
+            }
         }
 
-        let after_values: Vec<i32> = targets.iter().map(|target| target.3(&eval)).collect();
-        for ((feature_name, initial_value), before_value, after_value) in
-            izip!(&initial_values, &before_values, &after_values)
+        let after_values = values(&mut evaluators);
+        for ((anchor_name, feature_name), initial_value, before_value, after_value) in
+            izip!(&names, &initial_values, &before_values, &after_values)
         {
             println!(
-                "- {:>max_feat_len$}: {:>4} ({:>4} against prev, {:>4} against init)",
-                feature_name,
+                "- {:>max_name_len$}: {:>4} ({:>4} against prev, {:>4} against init)",
+                format!("{} ({})", feature_name, anchor_name),
                 after_value,
                 prettier_diff(after_value - before_value),
                 prettier_diff(after_value - initial_value)
             );
         }
+
+        evaluators.save(&output);
+        println!("> saved to {}", output);
         println!();
     }
 }
@@ -0,0 +1,125 @@
+use core::{
+    board::Board,
+    chain::Chain,
+    color::PuyoColor,
+    placement::Placement,
+    player_state::PlayerState,
+    search::ComplementedPuyo,
+    tumo::{Tumo, Tumos},
+};
+use std::{collections::BTreeMap, time::Instant};
+
+use bot::{
+    evaluator::Evaluator,
+    searcher::{SearchLimit, Searcher},
+    tumo_pattern, Nova,
+};
+use itertools::Itertools;
+
+/// Number of visible tumos in rollouts.
+const VISIBLE: usize = 3;
+
+/// One tumos for each color pattern of the first `len` tumos, ordered by the pattern.
+pub fn canonical_patterns(len: usize) -> Vec<Vec<Tumo>> {
+    let mut patterns = BTreeMap::new();
+    for colors in (0..len * 2)
+        .map(|_| PuyoColor::normal_colors().iter())
+        .multi_cartesian_product()
+    {
+        let tumos: Vec<Tumo> = colors
+            .chunks(2)
+            .map(|pair| Tumo::new(*pair[0], *pair[1]))
+            .collect();
+        patterns.entry(tumo_pattern(&tumos)).or_insert(tumos);
+    }
+    patterns.into_values().collect()
+}
+
+/// Place `tumos` from the empty board, searching `millis` for each with only the rest of `tumos` visible.
+/// Returns the board and the placements.
+pub fn search_line(
+    searcher: &dyn Searcher,
+    evaluator: &Evaluator,
+    tumos: &[Tumo],
+    millis: u64,
+) -> (Board, Vec<Placement>) {
+    let mut board = Board::new();
+    let mut placements = Vec::with_capacity(tumos.len());
+
+    for i in 0..tumos.len() {
+        let player_state = PlayerState {
+            board: board.clone(),
+            ..PlayerState::initial_state(Tumos::new(&tumos[i..]))
+        };
+        let limit = SearchLimit::from_millis(Instant::now(), millis);
+        let decision = searcher.search(&player_state, evaluator, &limit);
+        let placement = *decision
+            .placements
+            .first()
+            .expect("searcher returned empty placement");
+
+        board.place_tumo(&tumos[i], &placement).unwrap();
+        board.simulate();
+        placements.push(placement);
+    }
+
+    (board, placements)
+}
+
+/// Average of the biggest chain scores `nova` fires (or can fire with a few more puyos)
+/// from `board` within `len` random tumos.
+pub fn rollout_score(nova: &Nova, board: &Board, rollouts: usize, len: usize) -> u32 {
+    let total: u32 = (0..rollouts)
+        .map(|_| {
+            let mut player_state = PlayerState {
+                board: board.clone(),
+                ..PlayerState::initial_state(Tumos::new_random())
+            };
+            let mut max_score = 0;
+
+            for _ in 0..len {
+                let decision = nova.think(&player_state.limit_visible_tumos(VISIBLE), None, None);
+                let Some(placement) = decision.placements.first() else {
+                    break;
+                };
+                if player_state
+                    .board
+                    .place_tumo(&player_state.tumos[0], placement)
+                    .is_none()
+                {
+                    break;
+                }
+
+                max_score = max_score.max(player_state.board.simulate().score());
+                if player_state.board.is_dead() {
+                    break;
+                }
+                player_state.tumos.rotate(VISIBLE);
+            }
+
+            player_state.board.detect_potential_chain(
+                3,
+                1,
+                |_board: Board, _fire_x: usize, _cp: ComplementedPuyo, chain: Chain| {
+                    max_score = max_score.max(chain.score());
+                },
+            );
+            max_score
+        })
+        .sum();
+
+    total / rollouts.max(1) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns() {
+        // AA, AB
+        assert_eq!(canonical_patterns(1).len(), 2);
+        // AA AA, AA AB, AA BB, AA BC, AB AA, AB AB, AB AC, AB CC, AB CD
+        assert_eq!(canonical_patterns(2).len(), 9);
+    }
+}
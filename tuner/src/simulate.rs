@@ -1,11 +1,15 @@
-use core::tumo::Tumos;
+use core::{
+    game::{Game, GameResult, Player},
+    player_state::PlayerState,
+    tumo::Tumos,
+};
 use std::{
     sync::{Arc, Mutex},
     thread,
 };
 
-use bot::{evaluator::EvaluatorOverrider, Nova};
-use simulator::simulate_1p;
+use bot::{evaluator::EvaluatorSet, Nova, NovaConfig, WorkerPool};
+use simulator::{simulate_1p, simulate_2p};
 
 #[derive(Default, Clone, Copy, PartialEq, Eq)]
 struct SimulateResult {
@@ -51,31 +55,58 @@ impl Ord for SimulateResult {
     }
 }
 
-pub fn select_best_evaluator_overrider(
-    overriders: Vec<EvaluatorOverrider>,
+/// Number of games each thread plays with each candidate.
+// TODO: pass 100 as parameter (number of tumo patterns)
+pub const GAMES_PER_THREAD: usize = 100;
+
+/// Number of tumo patterns each thread plays for each pair of candidates in 2p.
+pub const GAMES_PER_THREAD_2P: usize = 4;
+
+/// Number of visible tumos in 2p games.
+const VISIBLE: usize = 3;
+
+/// Simulate `config` with each of `candidates` on `threads` threads, and return the best one.
+/// The j-th game of the i-th thread is played with the tumos of `seed + i * GAMES_PER_THREAD + j`.
+pub fn select_best_evaluators(
+    config: &NovaConfig,
+    candidates: Vec<EvaluatorSet>,
+    target_score: u32,
+    threads: usize,
+    seed: u32,
+) -> EvaluatorSet {
+    let best =
+        select_best_evaluators_index(config, candidates.clone(), target_score, threads, seed);
+    candidates[best]
+}
+
+fn select_best_evaluators_index(
+    config: &NovaConfig,
+    candidates: Vec<EvaluatorSet>,
     target_score: u32,
-) -> EvaluatorOverrider {
-    let n = overriders.len();
+    threads: usize,
+    seed: u32,
+) -> usize {
+    let n = candidates.len();
     let simulate_results = Arc::new(Mutex::new(vec![SimulateResult::default(); n]));
 
     let mut handles = vec![];
-    // TODO: pass 20 as parameter (threads)
-    for _ in 0..20 {
+    for thread in 0..threads {
         let all_v = Arc::clone(&simulate_results);
-        let overriders = overriders.clone();
+        let candidates = candidates.clone();
+        let config = config.clone();
 
         handles.push(thread::spawn(move || {
             let mut sim_v = vec![SimulateResult::default(); n];
+            // games are already run in parallel, so each search is single-threaded
+            let pool = Arc::new(WorkerPool::new(1));
 
-            // TODO: pass 100 as parameter (number of tumo patterns)
-            for _ in 0..100 {
-                let tumos = Tumos::new_random();
-                for (i, overrider) in overriders.iter().enumerate() {
-                    let result = simulate_1p(
-                        Nova::with_evaluator_overrider(*overrider),
-                        Some(tumos.clone()),
-                        None,
-                    );
+            for game in 0..GAMES_PER_THREAD {
+                let seed = seed.wrapping_add((thread * GAMES_PER_THREAD + game) as u32);
+                let tumos = Tumos::from_seed(seed);
+                for (i, evaluators) in candidates.iter().enumerate() {
+                    let nova =
+                        Nova::with_pool(config.clone().with_evaluators(*evaluators), pool.clone());
+                    let result = simulate_1p(&nova, Some(tumos.clone()), None);
                     sim_v[i] = sim_v[i]
                         + SimulateResult {
                             chain_success: if result.max_chain.score() >= target_score {
@@ -102,10 +133,78 @@ pub fn select_best_evaluator_overrider(
         handle.join().unwrap();
     }
 
-    *overriders
-        .iter()
-        .enumerate()
-        .max_by_key(|(index, _)| (*simulate_results.lock().unwrap()).get(*index).cloned())
-        .unwrap()
-        .1
+    let results = simulate_results.lock().unwrap();
+    (0..n).max_by_key(|&i| results[i]).unwrap()
+}
+
+/// Play 2p games of `config` with every pair of `candidates` on `threads` threads,
+/// and return the one with the most wins. Ties are broken by the later one.
+/// Games start from `initial_state` with the tumos taken as [select_best_evaluators] with
+/// [GAMES_PER_THREAD_2P], and each pair plays each tumos from both sides.
+pub fn select_best_evaluators_2p(
+    config: &NovaConfig,
+    candidates: Vec<EvaluatorSet>,
+    initial_state: &PlayerState,
+    threads: usize,
+    seed: u32,
+) -> EvaluatorSet {
+    let n = candidates.len();
+    let wins = Arc::new(Mutex::new(vec![0; n]));
+
+    let mut handles = vec![];
+    for thread in 0..threads {
+        let all_wins = Arc::clone(&wins);
+        let candidates = candidates.clone();
+        let config = config.clone();
+        let initial_state = initial_state.clone();
+
+        handles.push(thread::spawn(move || {
+            // games are already run in parallel, so each search is single-threaded
+            let pool = Arc::new(WorkerPool::new(1));
+            let novas: Vec<Nova> = candidates
+                .iter()
+                .map(|evaluators| {
+                    Nova::with_pool(config.clone().with_evaluators(*evaluators), pool.clone())
+                })
+                .collect();
+
+            let mut thread_wins = vec![0; n];
+            for game in 0..GAMES_PER_THREAD_2P {
+                let seed = seed.wrapping_add((thread * GAMES_PER_THREAD_2P + game) as u32);
+                let player_state = PlayerState {
+                    tumos: Tumos::from_seed(seed),
+                    ..initial_state.clone()
+                };
+
+                for i in 0..n {
+                    for j in (0..n).filter(|&j| j != i) {
+                        let game = Game::from_player_states(
+                            player_state.clone(),
+                            player_state.clone(),
+                            VISIBLE,
+                            seed as u64,
+                        );
+                        match simulate_2p(&novas[i], &novas[j], game, None) {
+                            GameResult::Win(Player::P1) => thread_wins[i] += 1,
+                            GameResult::Win(Player::P2) => thread_wins[j] += 1,
+                            GameResult::Draw => {}
+                        }
+                    }
+                }
+            }
+
+            let mut lock = all_wins.lock().unwrap();
+            for (wins, thread_wins) in lock.iter_mut().zip(thread_wins) {
+                *wins += thread_wins;
+            }
+        }));
+    }
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let wins = wins.lock().unwrap();
+    let best = (0..n).max_by_key(|&i| wins[i]).unwrap();
+    candidates[best]
 }
@@ -1,20 +1,26 @@
-use core::ojama::FPS;
-use std::io::Write;
+use std::{io::Write, time::Instant};
 
-use bot::Nova;
+use bot::{searcher::SearchLimit, Nova, NovaConfig};
 use log::info;
 use nova_upi::upi::{Upi, UpiMixin};
 
 fn main() {
     setup_logger();
     info!("nova-upi launched");
+    let mut config = NovaConfig::from_args();
+    info!("config: {:?}", config);
 
     Upi::receive_upi();
     Upi::send_ids();
+    Upi::send_options();
     Upi::send_upiok();
 
-    Upi::receive_isready();
-    let nova = Nova::default();
+    let options = Upi::receive_isready();
+    info!("options: {:?}", options);
+    if let Some(threads) = options.threads {
+        config = config.with_threads(threads);
+    }
+    let nova = Nova::new(config);
     Upi::send_readyok();
 
     loop {
@@ -29,9 +35,9 @@ fn main() {
                 player_states.expect("player_states should be present");
 
             let think_ms = Upi::receive_go();
-            let think_frame = think_ms * FPS / 1000;
+            let limit = SearchLimit::from_millis(Instant::now(), think_ms as u64);
 
-            let decision = nova.think(&player_state_1p, Some(&player_state_2p), Some(think_frame));
+            let decision = nova.think_with_limit(&player_state_1p, Some(&player_state_2p), &limit);
             let placement = decision.placements.first().unwrap();
             Upi::send_bestmove(*placement);
         }
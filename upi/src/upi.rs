@@ -1,19 +1,52 @@
-use core::{board::Board, placement::Placement, player_state::PlayerState, tumo::Tumos};
+use core::{
+    board::Board, ojama::ZENKESHI_BONUS, placement::Placement, player_state::PlayerState,
+    tumo::Tumos,
+};
 use std::io::Write;
 
-use log::{debug, info};
+use log::{debug, info, warn};
 
 const NOVA_NAME: &str = "nova";
 const NOVA_AUTHOR: &str = "morioprog";
 
 pub struct Upi;
 
+/// Options set by `setoption` before `isready`.
+#[derive(Default, Debug, PartialEq)]
+pub struct UpiOptions {
+    /// Number of search threads. All available threads are used if None.
+    pub threads: Option<usize>,
+}
+
+impl UpiOptions {
+    /// Apply `setoption name <name> value <value>`.
+    pub fn apply(&mut self, params: &[&str]) {
+        let ["name", name, "value", value] = params else {
+            panic!(
+                "`setoption` should be `setoption name <name> value <value>`, got {:?}",
+                params
+            );
+        };
+
+        match *name {
+            "Threads" => {
+                let threads: usize = value.parse().expect("Threads parse failed");
+                assert!(threads > 0, "Threads should be positive");
+                self.threads = Some(threads);
+            }
+            _ => warn!("unknown option `{}`, ignored", name),
+        }
+    }
+}
+
 pub trait UpiMixin {
     fn receive_upi();
     fn send_ids();
+    fn send_options();
     fn send_upiok();
 
-    fn receive_isready();
+    /// Receive `setoption`s until `isready`.
+    fn receive_isready() -> UpiOptions;
     fn send_readyok();
 
     fn receive_upinewgame();
@@ -55,14 +88,29 @@ impl UpiMixin for Upi {
         Self::print_and_flush(format!("id author {}", NOVA_AUTHOR));
     }
 
+    fn send_options() {
+        Self::print_and_flush("option name Threads type spin min 1");
+    }
+
     fn send_upiok() {
         Self::print_and_flush("upiok");
     }
 
-    fn receive_isready() {
-        debug!("waiting for `isready`");
-        let input = Self::read_stdin();
-        assert_eq!(input, "isready", "expected `isready`, received `{}`", input)
+    fn receive_isready() -> UpiOptions {
+        let mut options = UpiOptions::default();
+
+        loop {
+            debug!("waiting for `setoption` or `isready`");
+            let input = Self::read_stdin();
+            let tokens: Vec<&str> = input.split(' ').collect();
+            let (cmd, params) = tokens.split_first().expect("received empty string");
+
+            match *cmd {
+                "isready" => return options,
+                "setoption" => options.apply(params),
+                _ => panic!("expected `setoption` or `isready`, received `{}`", input),
+            }
+        }
     }
 
     fn send_readyok() {
@@ -108,50 +156,7 @@ impl UpiMixin for Upi {
         }
 
         // handle "position"
-        assert_eq!(
-            params.len(),
-            14,
-            "`position` should have 14 parameters (actual: {})",
-            params.len()
-        );
-
-        let board_1p: Board = Board::from_pfen(params[0]);
-        let tumos_1p: Tumos = Tumos::from(params[1]);
-        let frame_1p: u32 = params[2].parse().expect("frame_1p parse failed");
-        let carry_over_1p: u32 = params[3].parse().expect("carry_over_1p parse failed");
-        let ojama_fixed_1p: u32 = params[4].parse().expect("ojama_fixed_1p parse failed");
-        let ojama_incoming_1p: u32 = params[5].parse().expect("ojama_incoming_1p parse failed");
-        let current_chain_1p: u32 = params[6].parse().expect("current_chain_1p parse failed");
-        let board_2p: Board = Board::from_pfen(params[7]);
-        let tumos_2p: Tumos = Tumos::from(params[8]);
-        let frame_2p: u32 = params[9].parse().expect("frame_2p parse failed");
-        let carry_over_2p: u32 = params[10].parse().expect("carry_over_2p parse failed");
-        let ojama_fixed_2p: u32 = params[11].parse().expect("ojama_fixed_2p parse failed");
-        let ojama_incoming_2p: u32 = params[12].parse().expect("ojama_incoming_2p parse failed");
-        let current_chain_2p: u32 = params[13].parse().expect("current_chain_2p parse failed");
-
-        let player_state_1p = PlayerState::new(
-            board_1p,
-            tumos_1p,
-            frame_1p,
-            0,
-            carry_over_1p,
-            ojama_fixed_1p,
-            ojama_incoming_1p,
-            current_chain_1p,
-        );
-        let player_state_2p = PlayerState::new(
-            board_2p,
-            tumos_2p,
-            frame_2p,
-            0,
-            carry_over_2p,
-            ojama_fixed_2p,
-            ojama_incoming_2p,
-            current_chain_2p,
-        );
-
-        (Some((player_state_1p, player_state_2p)), None)
+        (Some(parse_position(params)), None)
     }
 
     fn receive_go() -> u32 {
@@ -178,3 +183,98 @@ impl UpiMixin for Upi {
         ));
     }
 }
+
+/// Parse the parameters of `position`, which are the fields of 1p followed by the ones of 2p.
+/// Each player has `board tumos frame carry_over ojama_fixed ojama_incoming current_chain zenkeshi`,
+/// where `zenkeshi` is 1 if the player holds an all-clear bonus, or 0 otherwise.
+///
+/// For backward compatibility, `zenkeshi` can be omitted from both players. In that case the bonus
+/// is assumed to be pending as a part of `carry_over` if it is at least [ZENKESHI_BONUS].
+fn parse_position(params: &[&str]) -> (PlayerState, PlayerState) {
+    assert!(
+        params.len() == 16 || params.len() == 14,
+        "`position` should have 16 (or 14 without zenkeshi) parameters (actual: {})",
+        params.len()
+    );
+
+    let (params_1p, params_2p) = params.split_at(params.len() / 2);
+    (
+        parse_player_state(params_1p, "1p"),
+        parse_player_state(params_2p, "2p"),
+    )
+}
+
+fn parse_player_state(params: &[&str], player: &str) -> PlayerState {
+    let parse = |i: usize, name: &str| -> u32 {
+        params[i]
+            .parse()
+            .unwrap_or_else(|_| panic!("{}_{} parse failed", name, player))
+    };
+
+    let mut player_state = PlayerState::new(
+        Board::from_pfen(params[0]),
+        Tumos::from(params[1]),
+        parse(2, "frame"),
+        0,
+        parse(3, "carry_over"),
+        parse(4, "ojama_fixed"),
+        parse(5, "ojama_incoming"),
+        parse(6, "current_chain"),
+    );
+
+    if let Some(zenkeshi) = params.get(7) {
+        player_state.zenkeshi = match *zenkeshi {
+            "1" => true,
+            "0" => false,
+            _ => panic!("zenkeshi_{} should be 0 or 1, got {}", player, zenkeshi),
+        };
+    } else if player_state.carry_over >= ZENKESHI_BONUS {
+        // the old form has no zenkeshi field, so the pending bonus comes as a part of carry_over
+        player_state.carry_over -= ZENKESHI_BONUS;
+        player_state.zenkeshi = true;
+    }
+
+    player_state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_threads() {
+        let mut options = UpiOptions::default();
+
+        options.apply(&["name", "Threads", "value", "4"]);
+        assert_eq!(options.threads, Some(4));
+
+        options.apply(&["name", "Unknown", "value", "1"]);
+        assert_eq!(options.threads, Some(4));
+    }
+
+    #[test]
+    fn parse_position_with_zenkeshi() {
+        let (player_state_1p, player_state_2p) = parse_position(&[
+            "r/r/////", "rgby", "10", "2100", "0", "0", "0", "1", //
+            "b/b/////", "bygr", "12", "30", "0", "0", "0", "0",
+        ]);
+
+        assert!(player_state_1p.zenkeshi);
+        assert_eq!(player_state_1p.carry_over, 2100);
+        assert!(!player_state_2p.zenkeshi);
+        assert_eq!(player_state_2p.carry_over, 30);
+    }
+
+    #[test]
+    fn parse_position_without_zenkeshi() {
+        let (player_state_1p, player_state_2p) = parse_position(&[
+            "r/r/////", "rgby", "10", "2130", "0", "0", "0", //
+            "b/b/////", "bygr", "12", "30", "0", "0", "0",
+        ]);
+
+        assert!(player_state_1p.zenkeshi);
+        assert_eq!(player_state_1p.carry_over, 2130 - ZENKESHI_BONUS);
+        assert!(!player_state_2p.zenkeshi);
+        assert_eq!(player_state_2p.carry_over, 30);
+    }
+}